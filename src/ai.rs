@@ -0,0 +1,346 @@
+//built-in artificial intelligence of enemies and bots for the hero
+//
+//Enemies hide out of the field, come to waiting positions and attack
+//the hero from them, shooting when they see him on their row or column.
+//Bots for the hero are used for tests and batch simulation.
+
+use rand::Rng;
+
+use crate::{Block, BlockType, Behaviour, Direct, EnimyState, HitTestType,
+            Robot, WIDTH_CELL_SIZE, HEIGHT_CELL_SIZE};
+use crate::boss;
+use crate::controller::{Command, Controller, GameView};
+use crate::rng::GameRng;
+
+//classic enemies, every one of them decides independently
+pub struct ClassicAi;
+
+impl ClassicAi {
+    pub fn new() -> ClassicAi {
+        ClassicAi
+    }
+
+    fn decide_enimy(&mut self, view: &GameView, enimy: &Robot,
+                                            rng: &mut GameRng) -> Command {
+        //boss follows its own script
+        if enimy.behaviour() == Behaviour::Boss {
+            return boss::decision(enimy, view.hero, view.map_size, rng);
+        }
+
+        let mut new_next_direct = enimy.next_direct;
+        let mut new_state = enimy.action_state;
+
+        match enimy.action_state {
+            //turrets stay in their hide cells and only shoot
+            _ if enimy.behaviour() == Behaviour::Turret => {
+                new_next_direct = Direct::NONE;
+            },
+            EnimyState::HideLeft => {
+                let decision = rng.gen_range(0, 500) <= 0;
+                if decision {
+                    new_next_direct = Direct::LEFT;
+                    new_state = EnimyState::ToWaitLeft;
+                } else {
+                    new_next_direct = Direct::NONE;
+                }
+            },
+            EnimyState::HideUp => {
+                let decision = rng.gen_range(0, 500) <= 0;
+                if decision {
+                    new_next_direct = Direct::UP;
+                    new_state = EnimyState::ToWaitUp;
+                } else {
+                    new_next_direct = Direct::NONE;
+                }
+            },
+            EnimyState::ToWaitLeft => {
+                if enimy.object.x == enimy.start_x - WIDTH_CELL_SIZE {
+                    new_next_direct = Direct::NONE;
+                    new_state = EnimyState::WaitLeft;
+                }
+            },
+            EnimyState::ToWaitUp => {
+                if enimy.object.y == enimy.start_y - HEIGHT_CELL_SIZE {
+                    new_next_direct = Direct::NONE;
+                    new_state = EnimyState::WaitUp;
+                }
+            },
+            EnimyState::WaitLeft => {
+                let hide_decision
+                            = rng.gen_range(0, 1000) <= 0;
+                let attack_decision
+                            = rng.gen_range(0, 200) <= 0;
+                if hide_decision {
+                    new_next_direct = Direct::RIGHT;
+                    new_state = EnimyState::ToHideLeft;
+                } else if attack_decision {
+                    new_next_direct = Direct::DOWN;
+                    new_state = EnimyState::Attack;
+                }
+            },
+            EnimyState::WaitUp => {
+                let hide_decision
+                            = rng.gen_range(0, 1000) <= 0;
+                let attack_decision
+                            = rng.gen_range(0, 200) <= 0;
+                if hide_decision {
+                    new_next_direct = Direct::DOWN;
+                    new_state = EnimyState::ToHideUp;
+                } else if attack_decision {
+                    new_next_direct = if view.hero.object.x >
+                                                    enimy.object.x {
+                        Direct::RIGHT
+                    } else {
+                        Direct::LEFT
+                    };
+                    new_state = EnimyState::Attack;
+                }
+            },
+            EnimyState::ToHideLeft => {
+                if (enimy.object.x == enimy.start_x) &&
+                                    (enimy.object.y == enimy.start_y) {
+                    new_next_direct = Direct::NONE;
+                    new_state = EnimyState::HideLeft;
+                }
+            },
+            EnimyState::ToHideUp => {
+                if (enimy.object.x == enimy.start_x) &&
+                                    (enimy.object.y == enimy.start_y) {
+                    new_next_direct = Direct::NONE;
+                    new_state = EnimyState::HideUp;
+                }
+            },
+            EnimyState::Attack => {
+                let mut node_touch = false;
+                let mut slide_touch = false;
+                for block in view.blocks {
+                    let intersect = block.object.rectangle_hit_test(
+                                        &enimy.object, HitTestType::INNER);
+
+                    if (block.block_type == BlockType::NODE) && intersect {
+                        node_touch = true;
+                    } else if (block.block_type == BlockType::SLIDE) &&
+                                                                intersect {
+                        slide_touch = true;
+                    }
+
+                }
+
+                if node_touch && !slide_touch {
+                    //new_next_direct
+                    let hero_object = &view.hero.object;
+
+                    let mut horizontal_should = false;
+                    let mut vertical_should = false;
+                    let mut horizontal_direct = Direct::NONE;
+                    let mut vertical_direct = Direct::NONE;
+
+                    if enimy.object.x >
+                                    hero_object.x + hero_object.width {
+                        horizontal_should = true;
+                        horizontal_direct = Direct::LEFT;
+                    }
+                    if enimy.object.x + enimy.object.width <
+                                                        hero_object.x {
+                        horizontal_should = true;
+                        horizontal_direct = Direct::RIGHT;
+                    }
+                    if enimy.object.y >
+                                    hero_object.y + hero_object.height {
+                        vertical_should = true;
+                        vertical_direct = Direct::UP;
+                    }
+                    if enimy.object.y + enimy.object.height <
+                                                        hero_object.y {
+                        vertical_should = true;
+                        vertical_direct = Direct::DOWN;
+                    }
+
+                    if horizontal_should && vertical_should {
+                        //true - horizontal, false - vertical
+                        let vec = rng.gen_range(0, 2) <= 0;
+                        if vec {
+                            new_next_direct = horizontal_direct;
+                        } else {
+                            new_next_direct = vertical_direct;
+                        }
+                    } else if horizontal_should {
+                        new_next_direct = horizontal_direct;
+                    } else if vertical_should {
+                        new_next_direct = vertical_direct;
+                    }
+                }
+
+            },
+            _ => {}
+        }
+
+        //fire control
+        let hero = view.hero;
+        let horizontal_see = (enimy.object.x < hero.object.x + hero.object.width) && (enimy.object.x + enimy.object.width > hero.object.x);
+
+        let vertical_see = (enimy.object.y < hero.object.y + hero.object.height) && (enimy.object.y + enimy.object.height > hero.object.y);
+
+        let fire_direct = if horizontal_see {
+            if enimy.object.y > hero.object.y {
+                Direct::UP
+            } else {
+                Direct::DOWN
+            }
+        } else if vertical_see {
+            if enimy.object.x > hero.object.x {
+                Direct::LEFT
+            } else {
+                Direct::RIGHT
+            }
+        } else {
+            Direct::NONE
+        };
+
+        Command {
+            direct: new_next_direct,
+            fire: fire_direct,
+            state: new_state
+        }
+    }
+}
+
+impl Controller for ClassicAi {
+    fn decide(&mut self, view: &GameView, robots: &[Robot],
+                                    rng: &mut GameRng) -> Vec<Command> {
+        robots.iter().map(|enimy| self.decide_enimy(view, enimy, rng))
+                                                                .collect()
+    }
+}
+
+//names of built-in bots for the hero
+pub const HERO_BOTS: [&str; 3] = ["sentry", "hunter", "random"];
+
+//create built-in bot for the hero by its name
+pub fn hero_bot(name: &str) -> Option<Box<dyn Controller>> {
+    match name {
+        "sentry" => Some(Box::new(SentryBot)),
+        "hunter" => Some(Box::new(HunterBot)),
+        "random" => Some(Box::new(RandomBot)),
+        _ => None
+    }
+}
+
+//direction of shooting at enemy on the same row or column with hero
+fn aim(hero: &Robot, enimies: &[Robot]) -> Direct {
+    let hero_body = hero.body();
+    for enimy in enimies {
+        let body = enimy.body();
+        let horizontal_see = (body.x < hero_body.x + hero_body.width) &&
+                                    (body.x + body.width > hero_body.x);
+        let vertical_see = (body.y < hero_body.y + hero_body.height) &&
+                                    (body.y + body.height > hero_body.y);
+
+        if horizontal_see {
+            return if body.y < hero_body.y { Direct::UP } else { Direct::DOWN };
+        } else if vertical_see {
+            return if body.x < hero_body.x { Direct::LEFT } else { Direct::RIGHT };
+        }
+    }
+    Direct::NONE
+}
+
+//check if hero can move from his cell in direction
+fn hero_passable(hero: &Robot, blocks: &[Block], direct: Direct) -> bool {
+    let (x, y) = match direct {
+        Direct::LEFT => (hero.object.x - WIDTH_CELL_SIZE, hero.object.y),
+        Direct::RIGHT => (hero.object.x + WIDTH_CELL_SIZE, hero.object.y),
+        Direct::UP => (hero.object.x, hero.object.y - HEIGHT_CELL_SIZE),
+        Direct::DOWN => (hero.object.x, hero.object.y + HEIGHT_CELL_SIZE),
+        Direct::NONE => return true
+    };
+
+    blocks.iter().any(|block| {
+        block.object.x == x && block.object.y == y &&
+            (block.block_type == BlockType::NODE ||
+                block.block_type == BlockType::SLIDE)
+    })
+}
+
+//hero stands on his place and shoots enemies on his row or column
+pub struct SentryBot;
+
+impl Controller for SentryBot {
+    fn decide(&mut self, view: &GameView, robots: &[Robot],
+                                    _rng: &mut GameRng) -> Vec<Command> {
+        robots.iter().map(|hero| Command {
+            direct: Direct::NONE,
+            fire: aim(hero, view.enimies),
+            state: hero.action_state
+        }).collect()
+    }
+}
+
+//hero hunts the nearest enemy: comes to its row or column and shoots
+pub struct HunterBot;
+
+impl HunterBot {
+    fn direct(hero: &Robot, view: &GameView) -> Direct {
+        let nearest = view.enimies.iter().min_by_key(|enimy| {
+            ((enimy.object.x - hero.object.x).abs() +
+                (enimy.object.y - hero.object.y).abs()) as i64
+        });
+        let enimy = match nearest {
+            Some(enimy) => enimy,
+            None => return Direct::NONE
+        };
+
+        let dx = enimy.object.x - hero.object.x;
+        let dy = enimy.object.y - hero.object.y;
+        let horizontal = if dx < 0.0 { Direct::LEFT } else { Direct::RIGHT };
+        let vertical = if dy < 0.0 { Direct::UP } else { Direct::DOWN };
+
+        //come to the nearer line first, turn if the way is blocked
+        let (first, second) = if dx.abs() <= dy.abs() {
+            (horizontal, vertical)
+        } else {
+            (vertical, horizontal)
+        };
+        if dx.abs() < WIDTH_CELL_SIZE || dy.abs() < HEIGHT_CELL_SIZE {
+            Direct::NONE
+        } else if hero_passable(hero, view.blocks, first) {
+            first
+        } else if hero_passable(hero, view.blocks, second) {
+            second
+        } else {
+            Direct::NONE
+        }
+    }
+}
+
+impl Controller for HunterBot {
+    fn decide(&mut self, view: &GameView, robots: &[Robot],
+                                    _rng: &mut GameRng) -> Vec<Command> {
+        robots.iter().map(|hero| Command {
+            direct: HunterBot::direct(hero, view),
+            fire: aim(hero, view.enimies),
+            state: hero.action_state
+        }).collect()
+    }
+}
+
+//hero moves and shoots randomly
+pub struct RandomBot;
+
+impl Controller for RandomBot {
+    fn decide(&mut self, _view: &GameView, robots: &[Robot],
+                                    rng: &mut GameRng) -> Vec<Command> {
+        const DIRECTS: [Direct; 5] = [Direct::NONE, Direct::UP, Direct::DOWN,
+                                        Direct::LEFT, Direct::RIGHT];
+        robots.iter().map(|hero| {
+            let mut command = Command::keep(hero);
+            if rng.gen_range(0, 30) <= 0 {
+                command.direct = DIRECTS[rng.gen_range(0, DIRECTS.len())];
+            }
+            if rng.gen_range(0, 10) <= 0 {
+                command.fire = DIRECTS[rng.gen_range(0, DIRECTS.len())];
+            }
+            command
+        }).collect()
+    }
+}
@@ -0,0 +1,306 @@
+//sounds and music
+//
+//Events of the last tick of the game are turned into sounds, mixer plays
+//them once per frame with volume of settings and plays looped music of level.
+//Sounds and music are synthesized from tones, so no files are needed.
+//Mixer plays samples through backend: audio device if the program is built
+//with feature "audio" and device is found, otherwise null backend which
+//plays nothing (headless runs, batch simulation and reinforcement learning).
+
+use crate::RobotType;
+use crate::events::{Damager, GameEvent};
+
+//samples per second of synthesized sounds
+pub const SAMPLE_RATE: u32 = 22050;
+
+//sound effects of the game
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy)]
+pub enum Sound {
+    Fire, //hero shoots
+    EnimyFire,
+    Impact, //bullet hits wall or another bullet
+    Hit, //enemy is damaged by bullet of hero
+    Kill, //enemy is destroyed by bullet of hero
+    Crash, //enemy is destroyed by collision
+    HeroDeath,
+    Pause,
+    Win,
+    Fail
+}
+
+//sound of event of the game, some events are silent
+pub fn sound(event: &GameEvent) -> Option<Sound> {
+    match *event {
+        GameEvent::ShotFired { robot_type: RobotType::Hero, .. } =>
+                                                            Some(Sound::Fire),
+        GameEvent::ShotFired { .. } => Some(Sound::EnimyFire),
+        GameEvent::BulletHitWall { .. } |
+        GameEvent::BulletsCancelled { .. } => Some(Sound::Impact),
+        GameEvent::EnemyHit { by: Damager::HeroBullet, .. } =>
+                                                            Some(Sound::Hit),
+        GameEvent::EnemyKilled { by: Damager::HeroBullet, .. } =>
+                                                            Some(Sound::Kill),
+        GameEvent::EnemyKilled { by: Damager::Hero, .. } |
+        GameEvent::EnemyKilled { by: Damager::Collision, .. } =>
+                                                            Some(Sound::Crash),
+        GameEvent::HeroHit { .. } => Some(Sound::HeroDeath),
+        GameEvent::LevelWon => Some(Sound::Win),
+        GameEvent::LevelFailed => Some(Sound::Fail),
+        _ => None
+    }
+}
+
+//output of samples, mono from -1.0 to 1.0
+pub trait AudioBackend {
+    fn play(&mut self, samples: &[f32], volume: f32);
+    //music is looped until it's stopped
+    fn play_music(&mut self, samples: &[f32], volume: f32);
+    fn set_music_volume(&mut self, volume: f32);
+    fn stop_music(&mut self);
+}
+
+//backend without output
+pub struct NullBackend;
+
+impl AudioBackend for NullBackend {
+    fn play(&mut self, _samples: &[f32], _volume: f32) {}
+    fn play_music(&mut self, _samples: &[f32], _volume: f32) {}
+    fn set_music_volume(&mut self, _volume: f32) {}
+    fn stop_music(&mut self) {}
+}
+
+pub struct Mixer {
+    backend: Box<dyn AudioBackend>,
+    effects_volume: f32, //from 0.0 to 1.0
+    music_volume: f32,
+    music: Option<usize>, //level which music is played
+    played: Vec<Sound>, //sounds played during current frame
+    cache: Vec<(Sound, Vec<f32>)> //synthesized sounds
+}
+
+impl Mixer {
+    pub fn new(backend: Box<dyn AudioBackend>) -> Mixer {
+        Mixer {
+            backend: backend,
+            effects_volume: 1.0,
+            music_volume: 1.0,
+            music: None,
+            played: vec![],
+            cache: vec![]
+        }
+    }
+
+    //audio device if it's available, null backend otherwise
+    pub fn with_default_backend() -> Mixer {
+        Mixer::new(default_backend())
+    }
+
+    //the same sound is played only once per frame
+    pub fn play(&mut self, sound: Sound) {
+        if self.played.contains(&sound) || self.effects_volume <= 0.0 {
+            return;
+        }
+        self.played.push(sound);
+
+        if !self.cache.iter().any(|&(cached, _)| cached == sound) {
+            self.cache.push((sound, synth(tones(sound))));
+        }
+        if let Some((_, samples)) = self.cache.iter()
+                                        .find(|&&(cached, _)| cached == sound) {
+            self.backend.play(samples, self.effects_volume);
+        }
+    }
+
+    //sounds of the next frame can be played again
+    pub fn end_frame(&mut self) {
+        self.played.clear();
+    }
+
+    //music of level or silence, the same music isn't restarted
+    pub fn set_music(&mut self, level: Option<usize>) {
+        if level == self.music {
+            return;
+        }
+        self.music = level;
+        match level {
+            Some(level) => {
+                let samples = synth(&music(level));
+                self.backend.play_music(&samples, self.music_volume);
+            },
+            None => self.backend.stop_music()
+        }
+    }
+
+    pub fn set_volume(&mut self, effects_volume: f32, music_volume: f32) {
+        self.effects_volume = effects_volume.max(0.0).min(1.0);
+        self.music_volume = music_volume.max(0.0).min(1.0);
+        self.backend.set_music_volume(self.music_volume);
+    }
+}
+
+#[cfg(feature = "audio")]
+fn default_backend() -> Box<dyn AudioBackend> {
+    match device::DeviceBackend::new() {
+        Some(backend) => Box::new(backend),
+        None => {
+            println!("Audio device is not found, the game is silent");
+            Box::new(NullBackend)
+        }
+    }
+}
+
+#[cfg(not(feature = "audio"))]
+fn default_backend() -> Box<dyn AudioBackend> {
+    Box::new(NullBackend)
+}
+
+//output to audio device by rodio
+#[cfg(feature = "audio")]
+mod device {
+    use rodio::{Device, Sink, Source};
+    use rodio::buffer::SamplesBuffer;
+
+    use super::{AudioBackend, SAMPLE_RATE};
+
+    pub struct DeviceBackend {
+        device: Device,
+        music: Option<Sink>
+    }
+
+    impl DeviceBackend {
+        pub fn new() -> Option<DeviceBackend> {
+            rodio::default_output_device()
+                .map(|device| DeviceBackend { device: device, music: None })
+        }
+    }
+
+    impl AudioBackend for DeviceBackend {
+        fn play(&mut self, samples: &[f32], volume: f32) {
+            let buffer = SamplesBuffer::new(1, SAMPLE_RATE, samples.to_vec());
+            rodio::play_raw(&self.device, buffer.amplify(volume));
+        }
+
+        fn play_music(&mut self, samples: &[f32], volume: f32) {
+            let buffer = SamplesBuffer::new(1, SAMPLE_RATE, samples.to_vec());
+            let sink = Sink::new(&self.device);
+            sink.set_volume(volume);
+            sink.append(buffer.repeat_infinite());
+            //previous music is stopped when its sink is dropped
+            self.music = Some(sink);
+        }
+
+        fn set_music_volume(&mut self, volume: f32) {
+            if let Some(sink) = &self.music {
+                sink.set_volume(volume);
+            }
+        }
+
+        fn stop_music(&mut self) {
+            self.music = None;
+        }
+    }
+}
+
+//waveform of tone
+#[derive(Clone, Copy)]
+enum Wave {
+    Square,
+    Triangle,
+    Noise
+}
+
+//tone is waveform, frequency in Hz (0 - silence), duration in milliseconds
+//and loudness
+type Tone = (Wave, f32, u32, f32);
+
+fn tones(sound: Sound) -> &'static [Tone] {
+    use self::Wave::*;
+
+    match sound {
+        Sound::Fire => &[(Square, 880.0, 30, 0.3), (Square, 660.0, 40, 0.25)],
+        Sound::EnimyFire => &[(Square, 330.0, 30, 0.2),
+                                (Square, 250.0, 40, 0.15)],
+        Sound::Impact => &[(Noise, 0.0, 40, 0.2)],
+        Sound::Hit => &[(Square, 520.0, 40, 0.3), (Square, 780.0, 50, 0.3)],
+        Sound::Kill => &[(Square, 300.0, 50, 0.35), (Square, 600.0, 50, 0.35),
+                            (Noise, 0.0, 120, 0.3)],
+        Sound::Crash => &[(Noise, 0.0, 180, 0.4)],
+        Sound::HeroDeath => &[(Square, 440.0, 120, 0.35),
+                                (Square, 330.0, 120, 0.35),
+                                (Square, 220.0, 160, 0.35),
+                                (Noise, 0.0, 250, 0.3)],
+        Sound::Pause => &[(Triangle, 660.0, 60, 0.4),
+                            (Triangle, 990.0, 80, 0.4)],
+        Sound::Win => &[(Square, 523.0, 120, 0.3), (Square, 659.0, 120, 0.3),
+                        (Square, 784.0, 120, 0.3), (Square, 1047.0, 350, 0.3)],
+        Sound::Fail => &[(Square, 392.0, 200, 0.3), (Square, 330.0, 200, 0.3),
+                            (Square, 262.0, 450, 0.3)]
+    }
+}
+
+//melodies of levels as semitones from A4 (None - rest), every note
+//lasts the same time
+const MELODIES: [&[Option<i32>]; 3] = [
+    &[Some(-9), None, Some(-5), None, Some(-2), None, Some(-5), None,
+        Some(-7), None, Some(-4), None, Some(0), None, Some(-4), None],
+    &[Some(-12), Some(-12), Some(0), Some(-12), Some(-10), Some(-10),
+        Some(2), Some(-10), Some(-9), Some(-9), Some(3), Some(-9),
+        Some(-14), Some(-14), Some(-2), Some(-14)],
+    &[Some(-16), None, Some(-16), Some(-13), Some(-16), None, Some(-16),
+        Some(-11), Some(-16), None, Some(-16), Some(-13), Some(-18), None,
+        Some(-17), None]
+];
+const NOTE_MILLIS: u32 = 180;
+
+//one loop of music of level
+fn music(level: usize) -> Vec<Tone> {
+    MELODIES[level%MELODIES.len()].iter()
+        .map(|&note| match note {
+            Some(semitone) => (Wave::Triangle,
+                                440.0*2f32.powf((semitone as f32)/12.0),
+                                NOTE_MILLIS, 0.25),
+            None => (Wave::Triangle, 0.0, NOTE_MILLIS, 0.0)
+        })
+        .collect()
+}
+
+//samples of tones, every tone fades out to avoid clicks
+fn synth(tones: &[Tone]) -> Vec<f32> {
+    let mut samples = vec![];
+    //noise is the same every time
+    let mut noise: u32 = 0x9e37_79b9;
+    for &(wave, frequency, millis, loudness) in tones {
+        let count = (SAMPLE_RATE*millis/1000) as usize;
+        for i in 0..count {
+            let time = (i as f32)/(SAMPLE_RATE as f32);
+            let phase = (time*frequency).fract();
+            let value = match wave {
+                Wave::Square => if phase < 0.5 { 1.0 } else { -1.0 },
+                Wave::Triangle => 4.0*(phase - 0.5).abs() - 1.0,
+                Wave::Noise => {
+                    noise ^= noise << 13;
+                    noise ^= noise >> 17;
+                    noise ^= noise << 5;
+                    (noise as f32)/(std::u32::MAX as f32)*2.0 - 1.0
+                }
+            };
+            let fade = 1.0 - (i as f32)/(count as f32);
+            let value = if frequency > 0.0 || is_noise(wave) {
+                value
+            } else {
+                0.0
+            };
+            samples.push(value*loudness*fade);
+        }
+    }
+    samples
+}
+
+fn is_noise(wave: Wave) -> bool {
+    match wave {
+        Wave::Noise => true,
+        _ => false
+    }
+}
@@ -0,0 +1,219 @@
+//batch simulation of many games for balancing experiments
+//
+//Games are played by a bot for the hero without window on several threads,
+//every game has its own seed, so any of them can be repeated.
+//Results of single games and their summary are written as CSV or JSON.
+
+use std::thread;
+
+use crate::{DeathCause, Game};
+use crate::ai;
+
+pub struct BatchConfig {
+    pub games: u64,
+    pub threads: usize,
+    pub first_seed: u64, //games use seeds from first_seed to first_seed+games
+    pub bot: String, //name of bot for the hero from ai::HERO_BOTS
+    pub squad_tactics: bool,
+    pub bullets_cancel: bool,
+    pub max_ticks: u64 //game is stopped after this count of ticks
+}
+
+//result of one game
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct GameResult {
+    pub seed: u64,
+    pub win: bool,
+    pub timeout: bool, //game was stopped before its end
+    pub ticks: u64,
+    pub kills: i64,
+    pub crashes: i64,
+    pub score: i64,
+    pub lives_left: i64,
+    pub deaths: Vec<DeathCause>
+}
+
+//aggregate results of all games
+#[derive(Debug)]
+pub struct Summary {
+    pub games: u64,
+    pub wins: u64,
+    pub timeouts: u64,
+    pub win_rate: f64,
+    pub average_ticks: f64, //of finished games
+    pub average_kills: f64,
+    pub average_crashes: f64,
+    pub average_score: f64,
+    pub deaths_by_own_bullet: u64,
+    pub deaths_by_enemy_bullet: u64,
+    pub deaths_by_free_bullet: u64,
+    pub deaths_by_contact: u64
+}
+
+impl Default for BatchConfig {
+    fn default() -> BatchConfig {
+        BatchConfig {
+            games: 1000,
+            threads: 4,
+            first_seed: 0,
+            bot: String::from("hunter"),
+            squad_tactics: false,
+            bullets_cancel: false,
+            max_ticks: 100_000
+        }
+    }
+}
+
+//play one game by bot from start to end or to the limit of ticks
+pub fn play(seed: u64, config: &BatchConfig) -> GameResult {
+    let mut game = Game::new();
+    game.reseed(seed);
+    game.set_squad_tactics(config.squad_tactics);
+    game.set_bullets_cancel(config.bullets_cancel);
+    game.set_hero_controller(ai::hero_bot(&config.bot));
+    game.create_level();
+
+    let mut ticks = 0;
+    while !game.game_over && ticks < config.max_ticks {
+        game.tick();
+        ticks += 1;
+    }
+
+    GameResult {
+        seed: seed,
+        win: game.game_win,
+        timeout: !game.game_over,
+        ticks: ticks,
+        kills: game.point_num,
+        crashes: game.crash_num,
+        score: game.score(),
+        lives_left: game.hero.lives,
+        deaths: game.death_causes.clone()
+    }
+}
+
+//play all games on several threads, results are ordered by seed
+pub fn run(config: &BatchConfig) -> Result<Vec<GameResult>, String> {
+    if ai::hero_bot(&config.bot).is_none() {
+        return Err(format!("Unknown bot '{}', possible bots: {}",
+                                    config.bot, ai::HERO_BOTS.join(", ")));
+    }
+
+    let threads = config.threads.max(1) as u64;
+    let mut handles = vec![];
+    for thread_num in 0..threads {
+        let thread_config = BatchConfig { bot: config.bot.clone(), ..*config };
+        let handle = thread::spawn(move || {
+            let config = thread_config;
+            let mut results = vec![];
+            let mut game_num = thread_num;
+            while game_num < config.games {
+                results.push(play(config.first_seed + game_num, &config));
+                game_num += threads;
+            }
+            results
+        });
+        handles.push(handle);
+    }
+
+    let mut results = vec![];
+    for handle in handles {
+        match handle.join() {
+            Ok(mut thread_results) => results.append(&mut thread_results),
+            Err(_) => return Err(String::from("Simulation thread panicked"))
+        }
+    }
+    results.sort_by_key(|result| result.seed);
+    Ok(results)
+}
+
+pub fn summarize(results: &[GameResult]) -> Summary {
+    let games = results.len() as u64;
+    let count = |cause: DeathCause| -> u64 {
+        results.iter()
+            .map(|result| result.deaths.iter()
+                                .filter(|&&death| death == cause).count())
+            .sum::<usize>() as u64
+    };
+    let average = |value: f64, count: u64| -> f64 {
+        if count > 0 { value/(count as f64) } else { 0.0 }
+    };
+
+    let wins = results.iter().filter(|result| result.win).count() as u64;
+    let timeouts = results.iter().filter(|result| result.timeout).count()
+                                                                    as u64;
+    let finished_ticks: u64 = results.iter()
+                                    .filter(|result| !result.timeout)
+                                    .map(|result| result.ticks).sum();
+    let kills: i64 = results.iter().map(|result| result.kills).sum();
+    let crashes: i64 = results.iter().map(|result| result.crashes).sum();
+    let score: i64 = results.iter().map(|result| result.score).sum();
+
+    Summary {
+        games: games,
+        wins: wins,
+        timeouts: timeouts,
+        win_rate: average(wins as f64, games),
+        average_ticks: average(finished_ticks as f64, games - timeouts),
+        average_kills: average(kills as f64, games),
+        average_crashes: average(crashes as f64, games),
+        average_score: average(score as f64, games),
+        deaths_by_own_bullet: count(DeathCause::OwnBullet),
+        deaths_by_enemy_bullet: count(DeathCause::EnemyBullet),
+        deaths_by_free_bullet: count(DeathCause::FreeBullet),
+        deaths_by_contact: count(DeathCause::Contact)
+    }
+}
+
+//one line per game with header
+pub fn results_csv(results: &[GameResult]) -> String {
+    let mut csv = String::from("seed,win,timeout,ticks,kills,crashes,score,\
+                                lives_left,deaths\n");
+    for result in results {
+        let deaths: Vec<String> = result.deaths.iter()
+                                    .map(|death| format!("{:?}", death))
+                                    .collect();
+        csv += &format!("{},{},{},{},{},{},{},{},{}\n",
+                    result.seed, result.win, result.timeout, result.ticks,
+                    result.kills, result.crashes, result.score,
+                    result.lives_left, deaths.join(" "));
+    }
+    csv
+}
+
+pub fn summary_csv(summary: &Summary) -> String {
+    format!("games,wins,timeouts,win_rate,average_ticks,average_kills,\
+            average_crashes,average_score,deaths_by_own_bullet,\
+            deaths_by_enemy_bullet,deaths_by_free_bullet,deaths_by_contact\n\
+            {},{},{},{:.4},{:.1},{:.3},{:.3},{:.1},{},{},{},{}\n",
+            summary.games, summary.wins, summary.timeouts, summary.win_rate,
+            summary.average_ticks, summary.average_kills,
+            summary.average_crashes, summary.average_score,
+            summary.deaths_by_own_bullet, summary.deaths_by_enemy_bullet,
+            summary.deaths_by_free_bullet, summary.deaths_by_contact)
+}
+
+pub fn summary_json(summary: &Summary) -> String {
+    format!("{{\n\
+            \x20 \"games\": {},\n\
+            \x20 \"wins\": {},\n\
+            \x20 \"timeouts\": {},\n\
+            \x20 \"win_rate\": {:.4},\n\
+            \x20 \"average_ticks\": {:.1},\n\
+            \x20 \"average_kills\": {:.3},\n\
+            \x20 \"average_crashes\": {:.3},\n\
+            \x20 \"average_score\": {:.1},\n\
+            \x20 \"deaths\": {{\n\
+            \x20   \"own_bullet\": {},\n\
+            \x20   \"enemy_bullet\": {},\n\
+            \x20   \"free_bullet\": {},\n\
+            \x20   \"contact\": {}\n\
+            \x20 }}\n\
+            }}\n",
+            summary.games, summary.wins, summary.timeouts, summary.win_rate,
+            summary.average_ticks, summary.average_kills,
+            summary.average_crashes, summary.average_score,
+            summary.deaths_by_own_bullet, summary.deaths_by_enemy_bullet,
+            summary.deaths_by_free_bullet, summary.deaths_by_contact)
+}
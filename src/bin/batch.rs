@@ -0,0 +1,78 @@
+//batch simulation of games without window
+//
+//Usage:
+//  batch [--games N] [--threads N] [--seed N] [--bot sentry|hunter|random]
+//        [--squad] [--cancel] [--max-ticks N] [--format csv|json]
+//        [--results FILE]
+//
+//Summary is printed in chosen format, results of single games are written
+//to FILE as CSV if it is set.
+
+extern crate crossfire;
+
+use std::env;
+use std::fs;
+use std::process;
+
+use crossfire::batch::{self, BatchConfig};
+
+fn parse_number(name: &str, value: Option<String>) -> u64 {
+    match value.as_ref().map(|value| value.parse::<u64>()) {
+        Some(Ok(number)) => number,
+        _ => {
+            eprintln!("Option {} needs a number", name);
+            process::exit(2);
+        }
+    }
+}
+
+fn main() {
+    let mut config = BatchConfig::default();
+    let mut format = String::from("json");
+    let mut results_file: Option<String> = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--games" => config.games = parse_number(&arg, args.next()),
+            "--threads" =>
+                config.threads = parse_number(&arg, args.next()) as usize,
+            "--seed" => config.first_seed = parse_number(&arg, args.next()),
+            "--max-ticks" => config.max_ticks = parse_number(&arg, args.next()),
+            "--squad" => config.squad_tactics = true,
+            "--cancel" => config.bullets_cancel = true,
+            "--bot" => config.bot = args.next().unwrap_or_default(),
+            "--format" => format = args.next().unwrap_or_default(),
+            "--results" => results_file = args.next(),
+            _ => {
+                eprintln!("Unknown option {}", arg);
+                process::exit(2);
+            }
+        }
+    }
+
+    let results = match batch::run(&config) {
+        Ok(results) => results,
+        Err(message) => {
+            eprintln!("{}", message);
+            process::exit(2);
+        }
+    };
+
+    if let Some(file) = results_file {
+        if let Err(error) = fs::write(&file, batch::results_csv(&results)) {
+            eprintln!("Can't write results to {}: {}", file, error);
+            process::exit(1);
+        }
+    }
+
+    let summary = batch::summarize(&results);
+    match format.as_str() {
+        "csv" => print!("{}", batch::summary_csv(&summary)),
+        "json" => print!("{}", batch::summary_json(&summary)),
+        _ => {
+            eprintln!("Unknown format {}, possible formats: csv, json", format);
+            process::exit(2);
+        }
+    }
+}
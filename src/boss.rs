@@ -0,0 +1,198 @@
+//scripted behaviour of boss
+//
+//Boss is a large flying robot, it patrols the lane over the field,
+//fires spread of bullets down the passages and charges down the columns.
+
+use rand::Rng;
+
+use crate::{Bullet, Direct, EnimyState, Robot,
+            WIDTH_CELL_SIZE, HEIGHT_CELL_SIZE};
+use crate::controller::Command;
+use crate::rng::GameRng;
+
+//row of the lane of patrol, it goes over the whole width of the map
+//between walls around it, boss charges down to the bottom wall
+pub const PATROL_ROW: f64 = 1.0;
+const CHARGE_SPEED: f64 = 4.0;
+
+const BULLET_SIZE: f64 = 8.0;
+
+//make decision for boss, its gun fires spread of bullets down the passages
+//on DOWN command and to both sides along its rows on LEFT or RIGHT command
+pub fn decision(boss: &Robot, hero: &Robot, map_size: [f64; 2],
+                                            rng: &mut GameRng) -> Command {
+    let [map_width, map_height] = map_size;
+    let patrol_y = PATROL_ROW*HEIGHT_CELL_SIZE;
+    let charge_y = map_height - HEIGHT_CELL_SIZE - boss.object.height;
+    let patrol = patrol_direct(boss, map_width);
+    let aligned = boss.object.x % WIDTH_CELL_SIZE == 0.0;
+
+    let (direct, fire, state) = match boss.action_state {
+        EnimyState::BossPatrol => {
+            if !aligned {
+                return Command::keep(boss);
+            }
+
+            let hero_below = (boss.object.x < hero.object.x + hero.object.width)
+                && (boss.object.x + boss.object.width > hero.object.x);
+            let charge_decision = hero_below &&
+                                rng.gen_range(0, 10) <= 0;
+            let spread_decision = rng.gen_range(0, 40) <= 0;
+
+            if charge_decision {
+                (Direct::DOWN, Direct::NONE, EnimyState::BossCharge)
+            } else if spread_decision {
+                (Direct::NONE, Direct::NONE, EnimyState::BossSpread)
+            } else {
+                (patrol, Direct::NONE, EnimyState::BossPatrol)
+            }
+        },
+        EnimyState::BossSpread => {
+            (patrol, Direct::DOWN, EnimyState::BossPatrol)
+        },
+        EnimyState::BossCharge => {
+            let hero_aside = (boss.object.y < hero.object.y + hero.object.height)
+                && (boss.object.y + boss.object.height > hero.object.y);
+            let fire = if hero_aside && boss.object.y % HEIGHT_CELL_SIZE == 0.0 {
+                Direct::LEFT
+            } else {
+                Direct::NONE
+            };
+
+            if boss.object.y >= charge_y {
+                (Direct::UP, fire, EnimyState::BossReturn)
+            } else {
+                (Direct::DOWN, fire, EnimyState::BossCharge)
+            }
+        },
+        EnimyState::BossReturn => {
+            if boss.object.y <= patrol_y {
+                (patrol, Direct::NONE, EnimyState::BossPatrol)
+            } else {
+                (Direct::UP, Direct::NONE, EnimyState::BossReturn)
+            }
+        },
+        _ => (Direct::NONE, Direct::NONE, EnimyState::BossPatrol)
+    };
+
+    Command { direct: direct, fire: fire, state: state }
+}
+
+//boss is faster during charge
+pub fn speed(boss: &Robot) -> f64 {
+    match boss.action_state {
+        EnimyState::BossCharge => CHARGE_SPEED,
+        _ => boss.robot_type.stats().speed
+    }
+}
+
+//fire by command of boss controller
+pub fn fire(boss: &mut Robot) {
+    match boss.prepare_fire {
+        Direct::DOWN => spread(boss),
+        Direct::LEFT | Direct::RIGHT => side_spread(boss),
+        _ => {}
+    }
+    boss.prepare_fire = Direct::NONE;
+}
+
+//keep moving along the lane, turn back on its ends
+fn patrol_direct(boss: &Robot, map_width: f64) -> Direct {
+    let left_x = WIDTH_CELL_SIZE;
+    let right_x = map_width - WIDTH_CELL_SIZE - boss.object.width;
+
+    match boss.direct {
+        Direct::LEFT if boss.object.x <= left_x => Direct::RIGHT,
+        Direct::LEFT => Direct::LEFT,
+        _ if boss.object.x >= right_x => Direct::LEFT,
+        _ => Direct::RIGHT
+    }
+}
+
+//fire bullets down every passage column under and near the boss,
+//passages are odd columns, there are no walls in them
+fn spread(boss: &mut Robot) {
+    let first_column = (boss.object.x/WIDTH_CELL_SIZE) as i64 - 2;
+    let last_column = ((boss.object.x + boss.object.width)
+                                            /WIDTH_CELL_SIZE) as i64 + 1;
+    let bullet_y = boss.object.y + boss.object.height + BULLET_SIZE;
+
+    for column in first_column..=last_column {
+        if column%2 == 0 || boss.bullets.len() >= boss.max_bullets {
+            continue;
+        }
+
+        let bullet_x = (column as f64)*WIDTH_CELL_SIZE +
+                                    WIDTH_CELL_SIZE/2.0 - BULLET_SIZE/2.0;
+        let bullet = Bullet::new(bullet_x, bullet_y, BULLET_SIZE, BULLET_SIZE,
+                                                                Direct::DOWN);
+        boss.bullets.push(bullet);
+    }
+}
+
+//fire bullets to the left and to the right along passage rows of the boss
+fn side_spread(boss: &mut Robot) {
+    let first_row = (boss.object.y/HEIGHT_CELL_SIZE) as i64;
+    let last_row = ((boss.object.y + boss.object.height)
+                                            /HEIGHT_CELL_SIZE) as i64 - 1;
+
+    for row in first_row..=last_row {
+        if row%2 == 0 || boss.bullets.len() + 2 > boss.max_bullets {
+            continue;
+        }
+
+        let bullet_y = (row as f64)*HEIGHT_CELL_SIZE +
+                                    HEIGHT_CELL_SIZE/2.0 - BULLET_SIZE/2.0;
+        let left_x = boss.object.x - BULLET_SIZE;
+        let right_x = boss.object.x + boss.object.width + BULLET_SIZE;
+        boss.bullets.push(Bullet::new(left_x, bullet_y,
+                                BULLET_SIZE, BULLET_SIZE, Direct::LEFT));
+        boss.bullets.push(Bullet::new(right_x, bullet_y,
+                                BULLET_SIZE, BULLET_SIZE, Direct::RIGHT));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RobotType;
+
+    //size of the largest map, 40x30 cells
+    const MAP_SIZE: [f64; 2] = [40.0*WIDTH_CELL_SIZE, 30.0*HEIGHT_CELL_SIZE];
+
+    fn boss_at(column: f64, row: f64, state: EnimyState) -> Robot {
+        Robot::new(column*WIDTH_CELL_SIZE, row*HEIGHT_CELL_SIZE,
+                                                    RobotType::Boss, state)
+    }
+
+    #[test]
+    fn patrol_turns_at_walls_of_map() {
+        let mut boss = boss_at(36.0, PATROL_ROW, EnimyState::BossPatrol);
+        boss.direct = Direct::RIGHT;
+        assert_eq!(patrol_direct(&boss, MAP_SIZE[0]), Direct::RIGHT);
+        boss.object.x = 37.0*WIDTH_CELL_SIZE;
+        assert_eq!(patrol_direct(&boss, MAP_SIZE[0]), Direct::LEFT);
+
+        boss.direct = Direct::LEFT;
+        boss.object.x = 2.0*WIDTH_CELL_SIZE;
+        assert_eq!(patrol_direct(&boss, MAP_SIZE[0]), Direct::LEFT);
+        boss.object.x = WIDTH_CELL_SIZE;
+        assert_eq!(patrol_direct(&boss, MAP_SIZE[0]), Direct::RIGHT);
+    }
+
+    #[test]
+    fn charge_stops_at_bottom_wall_of_map() {
+        let hero = Robot::new(0.0, 0.0, RobotType::Hero, EnimyState::Manual);
+        let mut rng = GameRng::new(1);
+
+        let boss = boss_at(19.0, 26.0, EnimyState::BossCharge);
+        let command = decision(&boss, &hero, MAP_SIZE, &mut rng);
+        assert_eq!(command.state, EnimyState::BossCharge);
+        assert_eq!(command.direct, Direct::DOWN);
+
+        let boss = boss_at(19.0, 27.0, EnimyState::BossCharge);
+        let command = decision(&boss, &hero, MAP_SIZE, &mut rng);
+        assert_eq!(command.state, EnimyState::BossReturn);
+        assert_eq!(command.direct, Direct::UP);
+    }
+}
@@ -0,0 +1,151 @@
+//camera over the map
+//
+//Camera shows part of the map of the size of the field view. It follows
+//the hero only when he leaves the dead zone in the middle of the view and
+//moves smoothly to the new position. Camera doesn't show anything beyond
+//the edges of the map, map smaller than the view is centered in it.
+//Minimap shows the whole map with robots and the part seen by camera.
+
+use crate::{BlockType, Game, WIDTH_CELL_SIZE, HEIGHT_CELL_SIZE};
+use crate::debug::Mark;
+
+//view of the map on logical screen, HUD is to the right of it
+pub const FIELD_VIEW_WIDTH: f64 = 17.0*WIDTH_CELL_SIZE;
+pub const FIELD_VIEW_HEIGHT: f64 = 14.0*HEIGHT_CELL_SIZE;
+
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Camera {
+    pub x: f64, //left top corner of view on the map
+    pub y: f64,
+    pub dead_zone: f64, //part of view where hero doesn't move camera
+    pub smoothing: f64, //part of distance to target passed every frame
+    placed: bool //camera was moved to the hero after reset
+}
+
+impl Camera {
+    pub fn new(dead_zone: f64, smoothing: f64) -> Camera {
+        Camera {
+            x: 0.0,
+            y: 0.0,
+            dead_zone: dead_zone,
+            smoothing: smoothing,
+            placed: false
+        }
+    }
+
+    //next follow jumps to target without smoothing
+    pub fn reset(&mut self) {
+        self.placed = false;
+    }
+
+    //move camera one frame to target (center of hero) on map of size
+    pub fn follow(&mut self, target_x: f64, target_y: f64,
+                                                    map_size: [f64; 2]) {
+        let (x, y) = if self.placed {
+            (self.x, self.y)
+        } else {
+            (target_x - FIELD_VIEW_WIDTH/2.0, target_y - FIELD_VIEW_HEIGHT/2.0)
+        };
+
+        let x = self.approach(x, target_x, FIELD_VIEW_WIDTH);
+        let y = self.approach(y, target_y, FIELD_VIEW_HEIGHT);
+        let (x, y) = (clamp(x, map_size[0], FIELD_VIEW_WIDTH),
+                        clamp(y, map_size[1], FIELD_VIEW_HEIGHT));
+
+        if self.placed {
+            self.x += (x - self.x)*self.smoothing;
+            self.y += (y - self.y)*self.smoothing;
+        } else {
+            self.x = x;
+            self.y = y;
+            self.placed = true;
+        }
+    }
+
+    //position of view along one axis which keeps target in dead zone
+    fn approach(&self, position: f64, target: f64, size: f64) -> f64 {
+        let half_zone = size*self.dead_zone/2.0;
+        let center = position + size/2.0;
+        if target < center - half_zone {
+            target + half_zone - size/2.0
+        } else if target > center + half_zone {
+            target - half_zone - size/2.0
+        } else {
+            position
+        }
+    }
+}
+
+//keep view inside map, small map is centered
+fn clamp(position: f64, map_size: f64, size: f64) -> f64 {
+    if map_size <= size {
+        (map_size - size)/2.0
+    } else {
+        position.max(0.0).min(map_size - size)
+    }
+}
+
+//marks of minimap of the whole map fitted into rectangle with camera frame
+pub fn minimap(game: &Game, camera: &Camera, rect: [f64; 4]) -> Vec<Mark> {
+    const BACK_COLOR: [f32; 4] = [0.1, 0.1, 0.1, 0.8];
+    const WALL_COLOR: [f32; 4] = [0.3, 0.3, 0.5, 1.0];
+    const FRAME_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.8];
+    //least size of robot on minimap to be seen
+    const DOT: f64 = 3.0;
+
+    let mut marks = vec![];
+    let map_size = game.map_size();
+    if map_size[0] <= 0.0 || map_size[1] <= 0.0 {
+        return marks;
+    }
+    let scale = (rect[2]/map_size[0]).min(rect[3]/map_size[1]);
+    let to_map = |x: f64, y: f64, width: f64, height: f64| {
+        [rect[0] + x*scale, rect[1] + y*scale, width*scale, height*scale]
+    };
+    marks.push(Mark {
+        rect: to_map(0.0, 0.0, map_size[0], map_size[1]),
+        color: BACK_COLOR
+    });
+
+    for block in &game.blocks {
+        if block.block_type == BlockType::WALL {
+            let obj = &block.object;
+            marks.push(Mark {
+                rect: to_map(obj.x, obj.y, obj.width, obj.height),
+                color: WALL_COLOR
+            });
+        }
+    }
+
+    //robots are dots in their colors
+    let robots = game.enimies.iter()
+                    .chain(Some(&game.hero).filter(|_| game.hero_on_field()));
+    for robot in robots {
+        let obj = &robot.object;
+        let center_x = rect[0] + (obj.x + obj.width/2.0)*scale;
+        let center_y = rect[1] + (obj.y + obj.height/2.0)*scale;
+        let size = (obj.width*scale).max(DOT);
+        marks.push(Mark {
+            rect: [center_x - size/2.0, center_y - size/2.0, size, size],
+            color: robot.robot_type.stats().color
+        });
+    }
+
+    //frame of part of map shown by camera
+    let view = to_map(camera.x.max(0.0), camera.y.max(0.0),
+                        FIELD_VIEW_WIDTH.min(map_size[0]),
+                        FIELD_VIEW_HEIGHT.min(map_size[1]));
+    let line = 1.0;
+    let frame = [
+        [view[0], view[1], view[2], line],
+        [view[0], view[1] + view[3] - line, view[2], line],
+        [view[0], view[1], line, view[3]],
+        [view[0] + view[2] - line, view[1], line, view[3]]
+    ];
+    for &rect in frame.iter() {
+        marks.push(Mark { rect: rect, color: FRAME_COLOR });
+    }
+
+    marks
+}
@@ -0,0 +1,48 @@
+//controllers of robots
+//
+//Controller makes decisions for a team of robots - the hero or enemies.
+//On every tick it gets read-only view of the game and returns command
+//for every robot of the team, so bots, test agents and alternative AIs
+//can drive robots without changes in the game loop.
+
+use crate::{Block, Bullet, Direct, EnimyState, Robot};
+use crate::rng::GameRng;
+
+//read-only view of the game for controllers
+pub struct GameView<'a> {
+    pub hero: &'a Robot,
+    pub enimies: &'a [Robot],
+    pub blocks: &'a [Block],
+    pub map_size: [f64; 2], //width and height of landscape
+    pub free_bullets: &'a [Bullet],
+    pub point_num: i64,
+    pub crash_num: i64,
+    pub score: i64
+}
+
+//decision for one robot on current tick
+#[derive(Debug)]
+#[derive(Clone, Copy)]
+pub struct Command {
+    pub direct: Direct, //direction of moving, NONE - stop
+    pub fire: Direct, //direction of shooting, NONE - don't shoot
+    pub state: EnimyState //state of behaviour after this tick
+}
+
+impl Command {
+    //command which lets robot keep doing what it does
+    pub fn keep(robot: &Robot) -> Command {
+        Command {
+            direct: robot.next_direct,
+            fire: Direct::NONE,
+            state: robot.action_state
+        }
+    }
+}
+
+pub trait Controller {
+    //return commands for controlled robots in the same order,
+    //random decisions should use generator of the game to be repeatable
+    fn decide(&mut self, view: &GameView, robots: &[Robot],
+                                    rng: &mut GameRng) -> Vec<Command>;
+}
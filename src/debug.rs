@@ -0,0 +1,257 @@
+//debug overlay
+//
+//Overlay shows what robots are doing: state of behaviour as colored
+//marker, chosen directions of moving and shooting, body used for hitting,
+//sides of robot touching impassable blocks, grid of cells and owners of
+//bullets. Labels near robots name their state, cell and chosen directions,
+//labels near bullets name their owners. Report prints the same information
+//as text.
+
+use crate::{Bullet, Direct, EnimyState, Game, GameObject, HitTestType, Robot,
+            RobotType, WIDTH_CELL_SIZE, HEIGHT_CELL_SIZE};
+
+//width of lines of overlay
+const LINE: f64 = 1.0;
+//width of strips showing sides of robot
+const SIDE: f64 = 3.0;
+
+const GRID_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.15];
+const BODY_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.9];
+const FREE_SIDE_COLOR: [f32; 4] = [0.0, 1.0, 0.0, 0.6];
+const BLOCKED_SIDE_COLOR: [f32; 4] = [1.0, 0.0, 0.0, 0.9];
+const MOVE_COLOR: [f32; 4] = [0.0, 1.0, 1.0, 1.0];
+const FIRE_COLOR: [f32; 4] = [1.0, 0.3, 0.0, 1.0];
+const FREE_BULLET_COLOR: [f32; 4] = [0.6, 0.6, 0.6, 1.0];
+//height of text of labels
+pub const LABEL_SIZE: u32 = 8;
+
+//rectangle of overlay to draw over the game
+#[derive(Debug)]
+#[derive(Clone, Copy)]
+pub struct Mark {
+    pub rect: [f64; 4], //x, y, width, height
+    pub color: [f32; 4]
+}
+
+//text of overlay, position is the left end of its baseline
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Label {
+    pub x: f64,
+    pub y: f64,
+    pub text: String,
+    pub color: [f32; 4]
+}
+
+//color of marker for state of behaviour
+pub fn state_color(state: EnimyState) -> [f32; 4] {
+    match state {
+        EnimyState::Manual => [1.0, 1.0, 1.0, 1.0],
+        EnimyState::HideLeft | EnimyState::HideUp => [0.3, 0.3, 1.0, 1.0],
+        EnimyState::ToWaitLeft | EnimyState::ToWaitUp => [0.0, 0.8, 0.8, 1.0],
+        EnimyState::WaitLeft | EnimyState::WaitUp => [0.0, 1.0, 0.0, 1.0],
+        EnimyState::ToHideLeft | EnimyState::ToHideUp => [0.8, 0.8, 0.0, 1.0],
+        EnimyState::Attack => [1.0, 0.0, 0.0, 1.0],
+        EnimyState::BossPatrol | EnimyState::BossReturn => [0.8, 0.5, 1.0, 1.0],
+        EnimyState::BossSpread | EnimyState::BossCharge => [1.0, 0.0, 1.0, 1.0]
+    }
+}
+
+//cell of center of object as column and row
+pub fn cell(object: &GameObject) -> (i64, i64) {
+    let column = (object.x + object.width/2.0)/WIDTH_CELL_SIZE;
+    let row = (object.y + object.height/2.0)/HEIGHT_CELL_SIZE;
+    (column.floor() as i64, row.floor() as i64)
+}
+
+//all marks of overlay in order of drawing
+pub fn overlay(game: &Game) -> Vec<Mark> {
+    let mut marks = vec![];
+
+    //grid over the landscape
+    let [width, height] = game.map_size();
+    let mut x = 0.0;
+    while x <= width {
+        marks.push(Mark { rect: [x, 0.0, LINE, height], color: GRID_COLOR });
+        x += WIDTH_CELL_SIZE;
+    }
+    let mut y = 0.0;
+    while y <= height {
+        marks.push(Mark { rect: [0.0, y, width, LINE], color: GRID_COLOR });
+        y += HEIGHT_CELL_SIZE;
+    }
+
+    robot_marks(&mut marks, game, &game.hero);
+    bullet_marks(&mut marks, &game.hero.bullets, game.hero.robot_type
+                                                            .stats().color);
+    for enimy in &game.enimies {
+        robot_marks(&mut marks, game, enimy);
+        bullet_marks(&mut marks, &enimy.bullets,
+                                            enimy.robot_type.stats().color);
+    }
+    bullet_marks(&mut marks, &game.free_bullets, FREE_BULLET_COLOR);
+
+    marks
+}
+
+//all labels of overlay
+pub fn labels(game: &Game) -> Vec<Label> {
+    let mut labels = vec![];
+
+    robot_labels(&mut labels, &game.hero);
+    bullet_labels(&mut labels, &game.hero.bullets, "hero",
+                                        game.hero.robot_type.stats().color);
+    for enimy in &game.enimies {
+        robot_labels(&mut labels, enimy);
+        let owner = format!("{:?}", enimy.robot_type);
+        bullet_labels(&mut labels, &enimy.bullets, &owner,
+                                            enimy.robot_type.stats().color);
+    }
+    bullet_labels(&mut labels, &game.free_bullets, "free", FREE_BULLET_COLOR);
+
+    labels
+}
+
+//state and cell over robot, chosen directions under it
+fn robot_labels(labels: &mut Vec<Label>, robot: &Robot) {
+    let obj = &robot.object;
+    let (column, row) = cell(obj);
+    let name = if robot.robot_type == RobotType::Hero {
+        String::from("hero")
+    } else {
+        format!("{:?}", robot.action_state)
+    };
+    labels.push(Label {
+        x: obj.x,
+        y: obj.y - 2.0,
+        text: format!("{} ({}, {})", name, column, row),
+        color: state_color(robot.action_state)
+    });
+    labels.push(Label {
+        x: obj.x,
+        y: obj.y + obj.height + LABEL_SIZE as f64,
+        text: format!("next {:?} fire {:?}", robot.next_direct,
+                                                        robot.prepare_fire),
+        color: MOVE_COLOR
+    });
+}
+
+fn bullet_labels(labels: &mut Vec<Label>, bullets: &Vec<Bullet>, owner: &str,
+                                                        color: [f32; 4]) {
+    for bullet in bullets {
+        let obj = &bullet.object;
+        labels.push(Label {
+            x: obj.x + obj.width + 3.0,
+            y: obj.y,
+            text: owner.to_string(),
+            color: color
+        });
+    }
+}
+
+fn robot_marks(marks: &mut Vec<Mark>, game: &Game, robot: &Robot) {
+    let obj = &robot.object;
+
+    //sides of cell of robot, red if robot can't move there
+    let sides = [
+        (HitTestType::LEFT, [obj.x, obj.y, SIDE, obj.height]),
+        (HitTestType::RIGHT, [obj.x + obj.width - SIDE, obj.y,
+                                                        SIDE, obj.height]),
+        (HitTestType::UP, [obj.x, obj.y, obj.width, SIDE]),
+        (HitTestType::DOWN, [obj.x, obj.y + obj.height - SIDE,
+                                                        obj.width, SIDE])
+    ];
+    for &(side, rect) in sides.iter() {
+        let blocked = game.blocks.iter().any(|block| {
+            !robot.can_pass(block.block_type) &&
+                            block.object.rectangle_hit_test(obj, side)
+        });
+        let color = if blocked { BLOCKED_SIDE_COLOR } else { FREE_SIDE_COLOR };
+        marks.push(Mark { rect: rect, color: color });
+    }
+
+    outline(marks, &robot.body(), BODY_COLOR);
+
+    //lines from center to chosen directions
+    let center_x = obj.x + obj.width/2.0;
+    let center_y = obj.y + obj.height/2.0;
+    direct_line(marks, center_x, center_y, robot.next_direct,
+                                            WIDTH_CELL_SIZE/2.0, MOVE_COLOR);
+    direct_line(marks, center_x, center_y, robot.prepare_fire,
+                                            WIDTH_CELL_SIZE*0.75, FIRE_COLOR);
+
+    //marker of state in the corner of cell
+    marks.push(Mark {
+        rect: [obj.x + SIDE, obj.y + SIDE, 6.0, 6.0],
+        color: state_color(robot.action_state)
+    });
+}
+
+//bullets are outlined by color of their owner
+fn bullet_marks(marks: &mut Vec<Mark>, bullets: &Vec<Bullet>,
+                                                        color: [f32; 4]) {
+    for bullet in bullets {
+        let obj = &bullet.object;
+        let around = GameObject {
+            x: obj.x - 2.0,
+            y: obj.y - 2.0,
+            width: obj.width + 4.0,
+            height: obj.height + 4.0
+        };
+        outline(marks, &around, color);
+    }
+}
+
+fn outline(marks: &mut Vec<Mark>, obj: &GameObject, color: [f32; 4]) {
+    let rects = [
+        [obj.x, obj.y, obj.width, LINE],
+        [obj.x, obj.y + obj.height - LINE, obj.width, LINE],
+        [obj.x, obj.y, LINE, obj.height],
+        [obj.x + obj.width - LINE, obj.y, LINE, obj.height]
+    ];
+    for &rect in rects.iter() {
+        marks.push(Mark { rect: rect, color: color });
+    }
+}
+
+fn direct_line(marks: &mut Vec<Mark>, x: f64, y: f64, direct: Direct,
+                                            length: f64, color: [f32; 4]) {
+    let rect = match direct {
+        Direct::LEFT => [x - length, y - LINE, length, LINE*2.0],
+        Direct::RIGHT => [x, y - LINE, length, LINE*2.0],
+        Direct::UP => [x - LINE, y - length, LINE*2.0, length],
+        Direct::DOWN => [x - LINE, y, LINE*2.0, length],
+        Direct::NONE => return
+    };
+    marks.push(Mark { rect: rect, color: color });
+}
+
+//text description of robots and bullets, one line for each of them
+pub fn report(game: &Game) -> String {
+    let mut text = String::new();
+    robot_report(&mut text, "hero", &game.hero);
+    for (num, enimy) in game.enimies.iter().enumerate() {
+        robot_report(&mut text, &format!("enimy {}", num), enimy);
+    }
+    for bullet in &game.free_bullets {
+        text += &format!("  free bullet {}\n", bullet_report(bullet));
+    }
+    text
+}
+
+fn robot_report(text: &mut String, name: &str, robot: &Robot) {
+    let (column, row) = cell(&robot.object);
+    *text += &format!("{} {:?} at ({}, {}) state {:?} direct {:?} \
+                        next {:?} fire {:?} lives {}\n",
+                        name, robot.robot_type, column, row,
+                        robot.action_state, robot.direct, robot.next_direct,
+                        robot.prepare_fire, robot.lives);
+    for bullet in &robot.bullets {
+        *text += &format!("  bullet {}\n", bullet_report(bullet));
+    }
+}
+
+fn bullet_report(bullet: &Bullet) -> String {
+    let (column, row) = cell(&bullet.object);
+    format!("at ({}, {}) {:?}", column, row, bullet.direct)
+}
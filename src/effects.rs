@@ -0,0 +1,221 @@
+//visual effects
+//
+//Effects are made from events of the game and don't change it: explosions
+//of destroyed robots, sparks of bullets hitting walls, other bullets
+//and damaged enemies,
+//fading outlines of destroyed robots, flash at the point of respawn of hero
+//and shaking of the field when hero loses life. They are used only by window
+//of the game, so headless runs don't have them. Effects have their own
+//random generator, so they don't change random decisions of the game.
+
+use rand::Rng;
+
+use crate::{Robot, RobotType};
+use crate::debug::Mark;
+use crate::events::GameEvent;
+use crate::rng::GameRng;
+
+const SPARK_COLOR: [f32; 4] = [1.0, 0.9, 0.5, 1.0];
+const CRASH_COLOR: [f32; 4] = [1.0, 0.6, 0.1, 1.0];
+const RESPAWN_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+//ticks of shaking of the field and its largest offset
+const SHAKE_TICKS: u32 = 20;
+const SHAKE_SIZE: f64 = 6.0;
+
+struct Particle {
+    x: f64,
+    y: f64,
+    speed_x: f64,
+    speed_y: f64,
+    size: f64,
+    color: [f32; 4],
+    life: u32, //ticks left
+    max_life: u32
+}
+
+//square outline which grows or shrinks and fades
+struct Ring {
+    x: f64, //center
+    y: f64,
+    from_size: f64,
+    to_size: f64,
+    color: [f32; 4],
+    life: u32,
+    max_life: u32
+}
+
+pub struct Effects {
+    particles: Vec<Particle>,
+    rings: Vec<Ring>,
+    shake: u32, //ticks of shaking left
+    rng: GameRng
+}
+
+impl Effects {
+    pub fn new() -> Effects {
+        Effects {
+            particles: vec![],
+            rings: vec![],
+            shake: 0,
+            rng: GameRng::new(0)
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.rings.clear();
+        self.shake = 0;
+    }
+
+    //start effects of event, hero is used for size of respawn flash
+    pub fn event(&mut self, event: &GameEvent, hero: &Robot) {
+        match *event {
+            GameEvent::BulletHitWall { x, y } => {
+                self.burst(x, y, SPARK_COLOR, 5, 2.0, 10, 2.0);
+            },
+            GameEvent::BulletsCancelled { x, y } => {
+                self.burst(x, y, SPARK_COLOR, 8, 2.5, 12, 2.0);
+            },
+            GameEvent::EnemyHit { robot_type, x, y, .. } => {
+                self.burst(x, y, robot_type.stats().color, 8, 2.5, 14, 3.0);
+            },
+            GameEvent::EnemyKilled { robot_type, x, y, .. } => {
+                self.explosion(robot_type, x, y);
+            },
+            GameEvent::EnemiesCrashed { x, y, .. } => {
+                self.burst(x, y, CRASH_COLOR, 10, 3.0, 16, 3.0);
+            },
+            GameEvent::HeroHit { x, y, .. } => {
+                self.explosion(RobotType::Hero, x, y);
+                self.shake = SHAKE_TICKS;
+            },
+            GameEvent::HeroRespawned { x, y } => {
+                let size = hero.object.width;
+                self.rings.push(Ring {
+                    x: x, y: y,
+                    from_size: size*3.0,
+                    to_size: size,
+                    color: RESPAWN_COLOR,
+                    life: 30,
+                    max_life: 30
+                });
+            },
+            GameEvent::BossAppeared { x, y } => {
+                self.rings.push(Ring {
+                    x: x, y: y,
+                    from_size: 16.0,
+                    to_size: 160.0,
+                    color: RobotType::Boss.stats().color,
+                    life: 40,
+                    max_life: 40
+                });
+            },
+            _ => {}
+        }
+    }
+
+    //move effects by one tick of the game
+    pub fn tick(&mut self) {
+        for particle in &mut self.particles {
+            particle.x += particle.speed_x;
+            particle.y += particle.speed_y;
+            //particles slow down
+            particle.speed_x *= 0.9;
+            particle.speed_y *= 0.9;
+            particle.life -= 1;
+        }
+        self.particles.retain(|particle| particle.life > 0);
+
+        for ring in &mut self.rings {
+            ring.life -= 1;
+        }
+        self.rings.retain(|ring| ring.life > 0);
+
+        if self.shake > 0 {
+            self.shake -= 1;
+        }
+    }
+
+    //offset of the field while it's shaking, it decreases to the end
+    pub fn shake(&mut self) -> (f64, f64) {
+        if self.shake == 0 {
+            return (0.0, 0.0);
+        }
+        let size = SHAKE_SIZE*(self.shake as f64)/(SHAKE_TICKS as f64);
+        (self.rng.gen_range(-size, size), self.rng.gen_range(-size, size))
+    }
+
+    //marks of effects in coordinates of the map
+    pub fn marks(&self) -> Vec<Mark> {
+        let mut marks = vec![];
+
+        for particle in &self.particles {
+            let mut color = particle.color;
+            color[3] *= (particle.life as f32)/(particle.max_life as f32);
+            let half = particle.size/2.0;
+            marks.push(Mark {
+                rect: [particle.x - half, particle.y - half,
+                        particle.size, particle.size],
+                color: color
+            });
+        }
+
+        for ring in &self.rings {
+            let part = 1.0 - (ring.life as f64)/(ring.max_life as f64);
+            let size = ring.from_size + (ring.to_size - ring.from_size)*part;
+            let half = size/2.0;
+            let (x, y) = (ring.x - half, ring.y - half);
+            let line = 2.0;
+            let mut color = ring.color;
+            color[3] *= (ring.life as f32)/(ring.max_life as f32);
+            let rects = [
+                [x, y, size, line],
+                [x, y + size - line, size, line],
+                [x, y, line, size],
+                [x + size - line, y, line, size]
+            ];
+            for &rect in rects.iter() {
+                marks.push(Mark { rect: rect, color: color });
+            }
+        }
+
+        marks
+    }
+
+    //destroyed robot explodes and its outline fades out
+    fn explosion(&mut self, robot_type: RobotType, x: f64, y: f64) {
+        let stats = robot_type.stats();
+        let size = stats.size;
+        self.burst(x, y, stats.color, 24, 4.0, 30, 4.0);
+        self.burst(x, y, SPARK_COLOR, 8, 2.0, 20, 2.0);
+        self.rings.push(Ring {
+            x: x, y: y,
+            from_size: size,
+            to_size: size*0.2,
+            color: stats.color,
+            life: 24,
+            max_life: 24
+        });
+    }
+
+    //particles flying from point in all directions
+    fn burst(&mut self, x: f64, y: f64, color: [f32; 4], count: usize,
+                                    speed: f64, life: u32, size: f64) {
+        for _ in 0..count {
+            let angle = self.rng.gen_range(0.0, 2.0*std::f64::consts::PI);
+            let speed = self.rng.gen_range(speed*0.3, speed);
+            let life = self.rng.gen_range(life/2, life + 1).max(1);
+            self.particles.push(Particle {
+                x: x,
+                y: y,
+                speed_x: angle.cos()*speed,
+                speed_y: angle.sin()*speed,
+                size: size,
+                color: color,
+                life: life,
+                max_life: life
+            });
+        }
+    }
+}
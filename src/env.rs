@@ -0,0 +1,295 @@
+//environment for reinforcement learning
+//
+//Gym-style wrapper around the game simulation: reset starts new episode
+//and returns observation, step applies action of the hero and returns
+//new observation, reward and flag of the end of episode.
+//It doesn't need window, so it can run headless for millions of steps.
+
+use crate::{BlockType, Bullet, Direct, Game, GameObject, Robot,
+            WIDTH_CELL_SIZE, HEIGHT_CELL_SIZE};
+use crate::controller::Command;
+use crate::events::{Damager, GameEvent};
+
+//channels of observation, every one of them is a grid of cells
+pub const CHANNEL_NODE: usize = 0;
+pub const CHANNEL_HOLE: usize = 1;
+pub const CHANNEL_WALL: usize = 2;
+pub const CHANNEL_SLIDE: usize = 3;
+pub const CHANNEL_HERO: usize = 4;
+pub const CHANNEL_ENIMY: usize = 5;
+pub const CHANNEL_HERO_BULLET: usize = 6;
+pub const CHANNEL_ENIMY_BULLET: usize = 7; //bullets of enemies and free ones
+pub const CHANNELS: usize = 8;
+
+//command of agent for the hero
+#[derive(Debug)]
+#[derive(Clone, Copy)]
+pub struct Action {
+    pub direct: Direct, //direction of moving, NONE - stop on crossroad
+    pub fire: Direct //direction of shooting, NONE - don't shoot
+}
+
+//grid encoding of the game, value is 1.0 if object occupies cell,
+//data is ordered by channels, then by rows, then by columns
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Observation {
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<f32>
+}
+
+//weights of reward for events during step
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Rewards {
+    pub kill: f64, //per enemy destroyed by hero bullets
+    pub crash: f64, //per enemy destroyed by crash with another enemy
+    pub life_lost: f64, //per life lost by hero
+    pub win: f64,
+    pub fail: f64
+}
+
+pub struct Env {
+    game: Game,
+    pub rewards: Rewards,
+    pub ticks_per_step: u32, //action is repeated for this count of ticks
+    pub max_ticks: u64, //episode is cut after this count of ticks, 0 - never
+    ticks: u64,
+    landscape: Observation //blocks don't change during episode
+}
+
+impl Observation {
+    fn new(width: usize, height: usize) -> Observation {
+        Observation {
+            width: width,
+            height: height,
+            data: vec![0.0; CHANNELS*width*height]
+        }
+    }
+
+    pub fn get(&self, channel: usize, column: usize, row: usize) -> f32 {
+        self.data[(channel*self.height + row)*self.width + column]
+    }
+
+    //mark cells covered by object
+    fn mark(&mut self, channel: usize, object: &GameObject) {
+        let first_column = (object.x/WIDTH_CELL_SIZE).floor().max(0.0);
+        let first_row = (object.y/HEIGHT_CELL_SIZE).floor().max(0.0);
+        let last_column = ((object.x + object.width)/WIDTH_CELL_SIZE).ceil();
+        let last_row = ((object.y + object.height)/HEIGHT_CELL_SIZE).ceil();
+
+        let last_column = (last_column as usize).min(self.width);
+        let last_row = (last_row as usize).min(self.height);
+        for row in (first_row as usize)..last_row {
+            for column in (first_column as usize)..last_column {
+                let index = (channel*self.height + row)*self.width + column;
+                self.data[index] = 1.0;
+            }
+        }
+    }
+
+    fn mark_bullets(&mut self, channel: usize, bullets: &Vec<Bullet>) {
+        for bullet in bullets {
+            self.mark(channel, &bullet.object);
+        }
+    }
+}
+
+impl Default for Rewards {
+    fn default() -> Rewards {
+        Rewards { kill: 1.0, crash: 0.5, life_lost: -1.0, win: 5.0, fail: -5.0 }
+    }
+}
+
+impl Env {
+    pub fn new() -> Env {
+        Env {
+            game: Game::new(),
+            rewards: Rewards::default(),
+            ticks_per_step: 1,
+            max_ticks: 0,
+            ticks: 0,
+            landscape: Observation::new(0, 0)
+        }
+    }
+
+    //start new episode, the same seed gives the same episode
+    //for the same actions
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.game.reseed(seed);
+        self.game.create_level();
+        self.ticks = 0;
+
+        let mut width = 0;
+        let mut height = 0;
+        for block in &self.game.blocks {
+            let obj = &block.object;
+            width = width.max(((obj.x + obj.width)/WIDTH_CELL_SIZE) as usize);
+            height = height.max(((obj.y + obj.height)/HEIGHT_CELL_SIZE)
+                                                                    as usize);
+        }
+
+        let mut landscape = Observation::new(width, height);
+        for block in &self.game.blocks {
+            let channel = match block.block_type {
+                BlockType::NODE => CHANNEL_NODE,
+                BlockType::HOLE => CHANNEL_HOLE,
+                BlockType::WALL => CHANNEL_WALL,
+                BlockType::SLIDE => CHANNEL_SLIDE
+            };
+            landscape.mark(channel, &block.object);
+        }
+        self.landscape = landscape;
+
+        self.observe()
+    }
+
+    //apply action of the hero, return observation, reward and end of episode
+    pub fn step(&mut self, action: &Action) -> (Observation, f64, bool) {
+        let command = Command {
+            direct: action.direct,
+            fire: action.fire,
+            state: self.game.hero.action_state
+        };
+        self.game.hero.obey(&command);
+
+        let mut reward = 0.0;
+        for _ in 0..self.ticks_per_step.max(1) {
+            self.game.tick();
+            self.ticks += 1;
+            reward += self.reward();
+            if self.game.game_over {
+                break;
+            }
+        }
+
+        let out_of_time = self.max_ticks > 0 && self.ticks >= self.max_ticks;
+        let done = self.game.game_over || out_of_time;
+        (self.observe(), reward, done)
+    }
+
+    //reward for events of the last tick, enemy destroyed by contact
+    //with hero gives nothing, hero only loses life
+    fn reward(&self) -> f64 {
+        let rewards = &self.rewards;
+        self.game.events().iter()
+            .map(|event| match event {
+                GameEvent::EnemyKilled { by: Damager::HeroBullet, .. } =>
+                                                                rewards.kill,
+                GameEvent::EnemyKilled { by: Damager::Collision, .. } =>
+                                                                rewards.crash,
+                GameEvent::HeroHit { .. } => rewards.life_lost,
+                GameEvent::LevelWon => rewards.win,
+                GameEvent::LevelFailed => rewards.fail,
+                _ => 0.0
+            })
+            .sum()
+    }
+
+    pub fn observe(&self) -> Observation {
+        let mut observation = self.landscape.clone();
+        let game = &self.game;
+
+        observation.mark(CHANNEL_HERO, &game.hero.body());
+        observation.mark_bullets(CHANNEL_HERO_BULLET, &game.hero.bullets);
+        for enimy in &game.enimies {
+            observation.mark(CHANNEL_ENIMY, &enimy.body());
+            observation.mark_bullets(CHANNEL_ENIMY_BULLET, &enimy.bullets);
+        }
+        observation.mark_bullets(CHANNEL_ENIMY_BULLET, &game.free_bullets);
+        observation
+    }
+
+    //count of ticks from the start of episode
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    pub fn hero(&self) -> &Robot {
+        &self.game.hero
+    }
+
+    pub fn game(&mut self) -> &mut Game {
+        &mut self.game
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EnimyState, RobotType};
+
+    const DIRECTS: [Direct; 5] = [Direct::UP, Direct::LEFT, Direct::NONE,
+                                    Direct::DOWN, Direct::RIGHT];
+
+    //scripted actions: hero turns every 40 ticks and shoots around
+    fn action(step: usize) -> Action {
+        Action {
+            direct: DIRECTS[(step/40) % DIRECTS.len()],
+            fire: DIRECTS[(step/7) % DIRECTS.len()]
+        }
+    }
+
+    //observations and rewards of episode
+    fn episode(env: &mut Env, seed: u64) -> Vec<(Vec<f32>, f64, bool)> {
+        let mut results = vec![(env.reset(seed).data, 0.0, false)];
+        for step in 0..3000 {
+            let (observation, reward, done) = env.step(&action(step));
+            results.push((observation.data, reward, done));
+            if done {
+                break;
+            }
+        }
+        results
+    }
+
+    #[test]
+    fn same_seed_and_actions_repeat_episode() {
+        let mut env = Env::new();
+        let first = episode(&mut env, 3);
+
+        //another environment which played other episode before
+        let mut other = Env::new();
+        episode(&mut other, 5);
+        let second = episode(&mut other, 3);
+
+        assert_eq!(first.len(), second.len());
+        for (step, (first, second)) in first.iter().zip(&second).enumerate() {
+            assert!(first == second, "episodes differ at step {}", step);
+        }
+    }
+
+    //enemy which touches hero and pair of enemies crashed far from him
+    fn crashes(env: &mut Env) -> f64 {
+        env.reset(3);
+        let game = env.game();
+        let (x, y) = (game.hero.object.x, game.hero.object.y);
+        let soldier = |x: f64, y: f64| {
+            Robot::new(x, y, RobotType::Soldier, EnimyState::WaitLeft)
+        };
+        game.enimies = vec![soldier(x + 16.0, y),
+                            soldier(96.0, 96.0), soldier(112.0, 96.0),
+                            soldier(416.0, 96.0)];
+        let (_, reward, _) = env.step(&Action { direct: Direct::NONE,
+                                                fire: Direct::NONE });
+        reward
+    }
+
+    #[test]
+    fn only_crashes_of_enemies_are_rewarded() {
+        let mut env = Env::new();
+        let reward = crashes(&mut env);
+        let rewards = &env.rewards;
+        assert_eq!(reward, rewards.life_lost + 2.0*rewards.crash);
+        assert_eq!(env.game().enimies.len(), 1);
+    }
+
+    #[test]
+    fn other_seed_gives_other_episode() {
+        let mut env = Env::new();
+        let first = episode(&mut env, 3);
+        let second = episode(&mut env, 4);
+        assert!(first != second);
+    }
+}
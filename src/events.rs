@@ -0,0 +1,141 @@
+//events of the game
+//
+//Every step the game collects events which happened during it: shots,
+//hits, kills, crashes, deaths of hero, changes of behaviour of enemies
+//and the end of level. Events of the last step are read by renderer, audio,
+//statistics and tests instead of comparing states of the game.
+//Positions are centers of objects at the moment of event.
+
+use crate::{DeathCause, Direct, EnimyState, GameObject, RobotType};
+
+//what damaged or destroyed enemy
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy)]
+pub enum Damager {
+    HeroBullet,
+    Hero, //contact with hero, it costs life of hero
+    Collision //with another enemy
+}
+
+#[derive(Debug)]
+#[derive(PartialEq)]
+#[derive(Clone, Copy)]
+pub enum GameEvent {
+    //robot of the type launched bullet, robot of hero has type Hero
+    ShotFired { robot_type: RobotType, x: f64, y: f64, direct: Direct },
+    BulletHitWall { x: f64, y: f64 },
+    //bullet of hero and hostile bullet destroyed each other
+    BulletsCancelled { x: f64, y: f64 },
+    //enemy lost life, but it's still alive
+    EnemyHit { robot_type: RobotType, x: f64, y: f64, by: Damager },
+    //state is behaviour of enemy at the moment of its destruction
+    EnemyKilled { robot_type: RobotType, x: f64, y: f64, by: Damager,
+                    state: EnimyState },
+    //two enemies touched each other and at least one of them is damaged
+    EnemiesCrashed { first: RobotType, second: RobotType, x: f64, y: f64 },
+    HeroHit { cause: DeathCause, x: f64, y: f64, lives: i64 },
+    //hero appeared again after losing life
+    HeroRespawned { x: f64, y: f64 },
+    //index of enemy in the game at the moment of change
+    EnemyStateChanged { index: usize, robot_type: RobotType,
+                        from: EnimyState, to: EnimyState },
+    BossAppeared { x: f64, y: f64 },
+    LevelWon,
+    LevelFailed
+}
+
+//center of object
+pub fn center(object: &GameObject) -> (f64, f64) {
+    (object.x + object.width/2.0, object.y + object.height/2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Game, Robot, WIDTH_CELL_SIZE, HEIGHT_CELL_SIZE};
+    use crate::controller::{Command, Controller, GameView};
+    use crate::rng::GameRng;
+
+    //robots stand on their places and shoot where script says
+    struct Script {
+        fire: fn(&GameView, &Robot) -> Direct
+    }
+
+    impl Controller for Script {
+        fn decide(&mut self, view: &GameView, robots: &[Robot],
+                                    _rng: &mut GameRng) -> Vec<Command> {
+            robots.iter().map(|robot| Command {
+                direct: Direct::NONE,
+                fire: (self.fire)(view, robot),
+                state: robot.action_state
+            }).collect()
+        }
+    }
+
+    //hero shoots soldier on his row, soldier above him shoots back once,
+    //then hero shoots it too
+    fn hero_fire(view: &GameView, _hero: &Robot) -> Direct {
+        if view.hero.lives == 3 {
+            if view.enimies.len() == 2 { Direct::LEFT } else { Direct::NONE }
+        } else {
+            Direct::UP
+        }
+    }
+
+    fn enimy_fire(view: &GameView, enimy: &Robot) -> Direct {
+        if enimy.object.y < view.hero.object.y && view.enimies.len() == 1 &&
+                                                    view.hero.lives == 3 {
+            Direct::DOWN
+        } else {
+            Direct::NONE
+        }
+    }
+
+    fn soldier(x_cell: i64, y_cell: i64) -> Robot {
+        Robot::new((x_cell as f64)*WIDTH_CELL_SIZE,
+                    (y_cell as f64)*HEIGHT_CELL_SIZE,
+                    RobotType::Soldier, EnimyState::WaitUp)
+    }
+
+    #[test]
+    fn scripted_game_events() {
+        let mut game = Game::new();
+        game.set_level(0);
+        game.create_level();
+        game.reseed(1);
+        game.boss_pending = false;
+        //hero starts at (9, 11), row and column of him are passages
+        game.enimies = vec![soldier(3, 11), soldier(9, 3)];
+        game.set_enimies_controller(Box::new(Script { fire: enimy_fire }));
+        game.set_hero_controller(Some(Box::new(Script { fire: hero_fire })));
+
+        let mut events = vec![];
+        for _ in 0..3000 {
+            game.step();
+            events.extend(game.events().iter().filter(|event| match event {
+                GameEvent::ShotFired { .. } | GameEvent::EnemyKilled { .. } |
+                GameEvent::HeroHit { .. } | GameEvent::LevelWon => true,
+                _ => false
+            }).cloned());
+            if game.game_over {
+                break;
+            }
+        }
+
+        let names: Vec<String> = events.iter().map(|event| match event {
+            GameEvent::ShotFired { robot_type, direct, .. } =>
+                                format!("shot {:?} {:?}", robot_type, direct),
+            GameEvent::EnemyKilled { robot_type, by, .. } =>
+                                format!("killed {:?} {:?}", robot_type, by),
+            GameEvent::HeroHit { cause, lives, .. } =>
+                                format!("hit {:?} {}", cause, lives),
+            event => format!("{:?}", event)
+        }).collect();
+        assert_eq!(names, vec![
+            "shot Hero LEFT", "killed Soldier HeroBullet",
+            "shot Soldier DOWN", "hit EnemyBullet 2",
+            "shot Hero UP", "killed Soldier HeroBullet", "LevelWon"]);
+        assert!(game.game_win);
+    }
+}
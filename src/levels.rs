@@ -0,0 +1,114 @@
+//levels of the game
+//
+//Every level sets size of its map in cells, the landscape is built from it:
+//walls around the map, field of crossroads and passages in the middle and
+//hiding places between them. Level sets start cell of hero and enemies:
+//every enemy is set by cell column, cell row, type and initial state.
+//Enemies over the field start hiding to the left, enemies on the sides
+//of the field start hiding up. Maps larger than the field view are scrolled.
+
+use crate::{EnimyState, RobotType};
+
+pub struct Level {
+    pub name: &'static str,
+    pub par_seconds: u64, //time of clearing, faster clearing gives bonus
+    pub columns: i64, //size of map in cells with walls around it
+    pub rows: i64,
+    pub hero: (i64, i64), //start cell of hero
+    pub spawns: &'static [(i64, i64, RobotType, EnimyState)]
+}
+
+pub const LEVELS: [Level; 4] = [
+    Level {
+        name: "Training",
+        par_seconds: 90,
+        columns: 17, rows: 14, hero: (9, 11),
+        spawns: &[
+            //over than field
+            (4, 1, RobotType::Soldier, EnimyState::HideLeft),
+            (8, 1, RobotType::Soldier, EnimyState::HideLeft),
+            (12, 1, RobotType::Soldier, EnimyState::HideLeft),
+            //lefter than field
+            (1, 4, RobotType::Soldier, EnimyState::HideUp),
+            (1, 10, RobotType::Soldier, EnimyState::HideUp),
+            //righter than field
+            (15, 6, RobotType::Soldier, EnimyState::HideUp),
+        ]
+    },
+    Level {
+        name: "Crossfire",
+        par_seconds: 150,
+        columns: 17, rows: 14, hero: (9, 11),
+        spawns: &[
+            //over than field
+            (4, 1, RobotType::Soldier, EnimyState::HideLeft),
+            (6, 1, RobotType::Scout, EnimyState::HideLeft),
+            (8, 1, RobotType::Tank, EnimyState::HideLeft),
+            (10, 1, RobotType::Soldier, EnimyState::HideLeft),
+            (12, 1, RobotType::Scout, EnimyState::HideLeft),
+            (14, 1, RobotType::Soldier, EnimyState::HideLeft),
+            //lefter than field
+            (1, 4, RobotType::Soldier, EnimyState::HideUp),
+            (1, 5, RobotType::Turret, EnimyState::HideUp),
+            (1, 8, RobotType::Tank, EnimyState::HideUp),
+            (1, 12, RobotType::Soldier, EnimyState::HideUp),
+            //righter than field
+            (15, 3, RobotType::Turret, EnimyState::HideUp),
+            (15, 6, RobotType::Scout, EnimyState::HideUp),
+            (15, 10, RobotType::Soldier, EnimyState::HideUp),
+        ]
+    },
+    Level {
+        name: "Fortress",
+        par_seconds: 240,
+        columns: 17, rows: 14, hero: (9, 11),
+        spawns: &[
+            //over than field
+            (4, 1, RobotType::Tank, EnimyState::HideLeft),
+            (6, 1, RobotType::Soldier, EnimyState::HideLeft),
+            (8, 1, RobotType::Tank, EnimyState::HideLeft),
+            (10, 1, RobotType::Scout, EnimyState::HideLeft),
+            (12, 1, RobotType::Tank, EnimyState::HideLeft),
+            (14, 1, RobotType::Soldier, EnimyState::HideLeft),
+            //lefter than field
+            (1, 3, RobotType::Turret, EnimyState::HideUp),
+            (1, 6, RobotType::Scout, EnimyState::HideUp),
+            (1, 9, RobotType::Tank, EnimyState::HideUp),
+            (1, 12, RobotType::Soldier, EnimyState::HideUp),
+            //righter than field
+            (15, 3, RobotType::Turret, EnimyState::HideUp),
+            (15, 5, RobotType::Soldier, EnimyState::HideUp),
+            (15, 8, RobotType::Turret, EnimyState::HideUp),
+            (15, 11, RobotType::Scout, EnimyState::HideUp),
+        ]
+    },
+    Level {
+        name: "Labyrinth",
+        par_seconds: 300,
+        columns: 40, rows: 30, hero: (19, 27),
+        spawns: &[
+            //over than field
+            (4, 1, RobotType::Soldier, EnimyState::HideLeft),
+            (8, 1, RobotType::Scout, EnimyState::HideLeft),
+            (12, 1, RobotType::Tank, EnimyState::HideLeft),
+            (16, 1, RobotType::Soldier, EnimyState::HideLeft),
+            (20, 1, RobotType::Scout, EnimyState::HideLeft),
+            (24, 1, RobotType::Soldier, EnimyState::HideLeft),
+            (28, 1, RobotType::Tank, EnimyState::HideLeft),
+            (32, 1, RobotType::Soldier, EnimyState::HideLeft),
+            (36, 1, RobotType::Scout, EnimyState::HideLeft),
+            //lefter than field
+            (1, 4, RobotType::Soldier, EnimyState::HideUp),
+            (1, 9, RobotType::Turret, EnimyState::HideUp),
+            (1, 14, RobotType::Tank, EnimyState::HideUp),
+            (1, 20, RobotType::Soldier, EnimyState::HideUp),
+            (1, 26, RobotType::Scout, EnimyState::HideUp),
+            //righter than field
+            (37, 4, RobotType::Scout, EnimyState::HideUp),
+            (37, 9, RobotType::Turret, EnimyState::HideUp),
+            (37, 14, RobotType::Soldier, EnimyState::HideUp),
+            (37, 20, RobotType::Tank, EnimyState::HideUp),
+            (37, 26, RobotType::Soldier, EnimyState::HideUp),
+        ]
+    }
+];
@@ -0,0 +1,2510 @@
+extern crate piston;
+extern crate graphics;
+extern crate opengl_graphics;
+
+//use piston::window::WindowSettings;
+use piston_window::*;
+//use piston::event_loop::*;
+//use piston::input::*;
+use opengl_graphics::{ GlGraphics, GlyphCache, OpenGL, Texture,
+                        TextureSettings };
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub mod controller;
+pub mod ai;
+pub mod squad;
+pub mod rng;
+pub mod env;
+pub mod batch;
+pub mod snapshot;
+pub mod rewind;
+pub mod debug;
+pub mod levels;
+pub mod screen;
+pub mod theme;
+pub mod view;
+pub mod camera;
+pub mod audio;
+pub mod events;
+pub mod effects;
+pub mod sweep;
+pub mod scores;
+pub mod scoring;
+pub mod stats;
+mod boss;
+mod records;
+use controller::{Command, Controller, GameView};
+use ai::ClassicAi;
+use squad::SquadAi;
+use rng::GameRng;
+use rewind::RewindBuffer;
+use levels::LEVELS;
+use screen::{HeroAction, Screen, ScreenState, Settings};
+use theme::{BulletOwner, Look, Theme};
+use view::{View, LOGICAL_WIDTH, LOGICAL_HEIGHT};
+use camera::{Camera, FIELD_VIEW_WIDTH, FIELD_VIEW_HEIGHT};
+use audio::{Mixer, Sound};
+use events::{center, Damager, GameEvent};
+use effects::Effects;
+use scores::HighScores;
+use scoring::Scoring;
+use stats::{GameStats, LifetimeStats};
+
+pub const WIDTH_CELL_SIZE: f64 = 32.0;
+pub const HEIGHT_CELL_SIZE: f64 = 32.0;
+
+const HEIGHT_HUD_SEGMENT_SIZE: f64 = 8.0;
+const WIDTH_HUD_SEGMENT_SIZE: f64 = 8.0;
+
+//font of all texts, it's bundled into the program
+const FONT: &[u8] = include_bytes!("../assets/DejaVuSans.ttf");
+const HUD_FONT_SIZE: u32 = 14;
+const HUD_LINE_HEIGHT: f64 = 20.0;
+//count of frames while notice for the player is shown
+const NOTICE_FRAMES: u32 = 120;
+
+//directory with save slots
+const SAVE_DIR: &str = "saves";
+
+//ticks of the game per second, one tick is done per frame
+const TICKS_PER_SECOND: usize = 60;
+//how far the game can be rewound in practice mode
+const REWIND_SECONDS: usize = 10;
+
+//part of field view where hero doesn't move camera
+const CAMERA_DEAD_ZONE: f64 = 0.3;
+//part of distance to hero passed by camera every frame
+const CAMERA_SMOOTHING: f64 = 0.15;
+
+//count of ticks while damaged robot flashes
+const HIT_FLASH_TICKS: u32 = 16;
+//count of ticks while damaged robot can't be damaged again
+const INVULNERABLE_TICKS: u32 = 24;
+//count of ticks while hero is away after losing life
+const RESPAWN_DELAY_TICKS: u32 = 60;
+//count of ticks while respawned hero blinks and can't be damaged
+const RESPAWN_INVULNERABLE_TICKS: u32 = 180;
+//the nearest enemy or bullet to safe point of respawn, in cells
+const SAFE_ENIMY_CELLS: f64 = 3.0;
+const SAFE_BULLET_CELLS: f64 = 2.0;
+
+//enemy is lured by hero while it chases him or for this count of ticks
+//after it, or after it turned away from bullet of hero
+const LURE_TICKS: u32 = 2*TICKS_PER_SECOND as u32;
+//bullet of hero flying to enemy within this count of cells makes it dodge
+const LURE_CELLS: f64 = 4.0;
+
+//difficulty of the game sets count of lives of hero
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy)]
+pub enum Difficulty {
+    Easy, Normal, Hard
+}
+
+pub const DIFFICULTIES: [Difficulty; 3] = [Difficulty::Easy,
+                                        Difficulty::Normal, Difficulty::Hard];
+
+//state of enemies with different behaviours in each of them
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy)]
+pub enum EnimyState {
+    Manual, HideLeft, HideUp,
+    ToWaitLeft, ToWaitUp, WaitLeft, WaitUp,
+    ToHideLeft, ToHideUp,
+    Attack,
+    BossPatrol, BossSpread, BossCharge, BossReturn
+}
+
+//type of robot, it sets stats, behaviour and view of robot
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy)]
+pub enum RobotType {
+    Hero, Soldier, Tank, Scout, Turret, Boss
+}
+
+//behaviour of enemies:
+//Raider - hides, waits and attacks
+//Turret - never leaves its hide cell, only shoots
+//Boss - flies over walls and follows its script
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy)]
+pub enum Behaviour {
+    Raider, Turret, Boss
+}
+
+//properties of every type of robots
+pub struct RobotStats {
+    pub cells: i64, //count of cells occupied by robot on each side
+    pub size: f64, //size of body inside occupied cells
+    pub speed: f64, //should be divisor of cell size
+    pub lives: i64,
+    pub bullet_speed: f64,
+    pub max_bullets: usize,
+    pub points: i64, //score for destroying robot
+    pub behaviour: Behaviour,
+    pub color: [f32; 4]
+}
+
+//directions for objects - robots, blocks and bullets
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy)]
+pub enum Direct {
+    NONE, UP, DOWN, LEFT, RIGHT
+}
+
+//reasons of losing life by hero
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy)]
+pub enum DeathCause {
+    OwnBullet, EnemyBullet,
+    FreeBullet, //bullet of destroyed enemy
+    Contact //collision with enemy
+}
+
+//possible arguments of hitTest function
+#[derive(Debug)]
+#[derive(Clone, Copy)]
+pub enum HitTestType {
+    FULL, INNER, UP, DOWN, LEFT, RIGHT
+}
+
+//type of blocks on landscape:
+//NODE - hero can pass and stand, enimies and bullets can fly over it
+//HOLE - hero can't pass, but enimies and bullets can fly over it
+//WALL - hero can't pass, enimies and bullets can't fly throw it
+//SLIDE - hero can move, but can't stand, enimies and bullets can fly over it
+#[derive(Debug)]
+#[derive(Clone, Copy)]
+#[derive(PartialEq, Eq)]
+pub enum BlockType {
+    NODE, HOLE, WALL, SLIDE
+}
+
+//geometrical properties of robots, bullets and blocks
+#[derive(Clone)]
+pub struct GameObject {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+//enemies and hero are robots
+#[derive(Clone)]
+pub struct Robot {
+    pub object: GameObject, //cell occupied by robot, used for moving
+    pub robot_type: RobotType,
+    pub start_x: f64,
+    pub start_y: f64,
+    pub direct: Direct,
+    pub next_direct: Direct,
+    pub speed: f64,
+    pub lives: i64,
+    pub bullet_speed :f64,
+    pub max_bullets: usize,
+    pub bullets: Vec<Bullet>,
+    pub prepare_fire: Direct,
+    pub action_state: EnimyState,
+    pub hit_flash: u32, //ticks left to flash after damage
+    pub invulnerable: u32, //ticks left while robot can't be damaged
+    pub lured: u32, //ticks left while crash of enemy is caused by hero
+    pub last_x: f64, //position at the start of tick, used for collisions
+    pub last_y: f64
+}
+
+#[derive(Clone)]
+pub struct Bullet {
+    pub object: GameObject,
+    pub direct: Direct,
+    pub last_x: f64, //position at the start of tick or where it was launched
+    pub last_y: f64
+}
+
+#[derive(Clone)]
+pub struct Block {
+    pub object: GameObject,
+    pub block_type: BlockType
+}
+
+pub struct Game {
+    hero :Robot,
+    blocks :Vec<Block>,
+    enimies :Vec<Robot>,
+    free_bullets :Vec<Bullet>,//bullets of died robots
+    point_num :i64, //count of killed enemies
+    crash_num :i64, //count of crashed enemies
+    scoring: Scoring, //score of hero by its sources
+    stats: GameStats, //statistics of the game
+    ticks: u64, //ticks from the start of level
+    level: usize, //index of level in levels::LEVELS
+    difficulty: Difficulty,
+    boss_pending: bool, //boss appears when other enemies are destroyed
+    death_causes: Vec<DeathCause>, //reasons of every life lost by hero
+    hero_respawn: u32, //ticks left until hero appears again, 0 - he's here
+    events: Vec<GameEvent>, //events of the last tick
+    squad_tactics: bool, //enemies are controlled as a squad
+    bullets_cancel: bool, //bullets of hero and enemies destroy each other
+    enimies_controller: Box<dyn Controller>,
+    hero_controller: Option<Box<dyn Controller>>, //None - manual control
+    rng: GameRng, //all random decisions of the game
+    paused: bool,
+    game_over: bool, //any reason, win or fail
+    game_win: bool //game over and win
+}
+
+pub struct App {
+    gl: GlGraphics, // OpenGL drawing backend.
+    glyphs: GlyphCache<'static>, //rendered characters of font
+    game :Game,
+    screens: Vec<ScreenState>, //stack of screens, top one is shown
+    settings: Settings,
+    theme: Theme,
+    sheet: Option<Texture>, //sprite sheet of theme
+    rebinding: bool, //waiting for new key of action on controls screen
+    quit: bool, //player chose to quit
+    save_slot: u32, //slot for saving and loading of the game
+    practice: bool, //practice mode, the game can be rewound
+    rewinding: bool, //rewind key is held
+    rewind: RewindBuffer,
+    time_scale: f64, //speed of the game, ticks per frame
+    frame_time: f64, //part of tick accumulated from previous frames
+    debug: bool, //debug overlay is shown
+    view: View, //placement of logical screen in the window
+    camera: Camera,
+    minimap: bool, //minimap is shown under indicators
+    mixer: Mixer,
+    effects: Effects,
+    scores: HighScores,
+    new_high_score: bool, //result of ended game gets into table of scores
+    player_name: String, //name for high scores, the last one is offered again
+    scores_level: usize, //table of high scores shown on its screen
+    scores_difficulty: Difficulty,
+    lifetime: LifetimeStats, //statistics of all games except practice
+    notice: Option<(String, u32)> //message for player and frames to show it
+}
+
+impl GameObject {
+    //check collision or touching of two objects
+    pub fn rectangle_hit_test(&self, check_obj: &GameObject,
+                            hit_type: HitTestType) -> bool {
+
+        let hit_left = match hit_type {
+            HitTestType::FULL | HitTestType::LEFT =>
+                                    self.x + self.width >= check_obj.x,
+            _ => self.x + self.width > check_obj.x
+        };
+        let hit_right = match hit_type {
+            HitTestType::FULL | HitTestType::RIGHT =>
+                                    self.x <= check_obj.x + check_obj.width,
+            _ => self.x < check_obj.x + check_obj.width
+        };
+        let hit_up = match hit_type {
+            HitTestType::FULL | HitTestType::UP =>
+                                    self.y + self.height >= check_obj.y,
+            _ => self.y + self.height > check_obj.y
+        };
+        let hit_down = match hit_type {
+            HitTestType::FULL | HitTestType::DOWN =>
+                                    self.y <= check_obj.y + check_obj.height,
+            _ => self.y < check_obj.y + check_obj.height
+        };
+
+        (hit_left && hit_right && hit_up && hit_down)
+    }
+}
+
+impl RobotType {
+    pub fn stats(self) -> RobotStats {
+        match self {
+            RobotType::Hero => RobotStats {
+                cells: 1, size: WIDTH_CELL_SIZE, speed: 2.0, lives: 3,
+                bullet_speed: 4.0, max_bullets: 1, points: 0,
+                behaviour: Behaviour::Raider, color: [0.0, 1.0, 0.0, 1.0]
+            },
+            RobotType::Soldier => RobotStats {
+                cells: 1, size: WIDTH_CELL_SIZE, speed: 2.0, lives: 1,
+                bullet_speed: 4.0, max_bullets: 1, points: 50,
+                behaviour: Behaviour::Raider, color: [1.0, 0.0, 0.0, 1.0]
+            },
+            RobotType::Tank => RobotStats {
+                cells: 1, size: WIDTH_CELL_SIZE, speed: 1.0, lives: 3,
+                bullet_speed: 3.0, max_bullets: 1, points: 120,
+                behaviour: Behaviour::Raider, color: [0.6, 0.0, 0.1, 1.0]
+            },
+            RobotType::Scout => RobotStats {
+                cells: 1, size: WIDTH_CELL_SIZE*0.75, speed: 4.0, lives: 1,
+                bullet_speed: 4.0, max_bullets: 1, points: 80,
+                behaviour: Behaviour::Raider, color: [1.0, 0.5, 0.0, 1.0]
+            },
+            RobotType::Turret => RobotStats {
+                cells: 1, size: WIDTH_CELL_SIZE*0.875, speed: 0.0, lives: 2,
+                bullet_speed: 6.0, max_bullets: 1, points: 100,
+                behaviour: Behaviour::Turret, color: [1.0, 0.0, 1.0, 1.0]
+            },
+            RobotType::Boss => RobotStats {
+                cells: 2, size: WIDTH_CELL_SIZE*2.0, speed: 2.0, lives: 12,
+                bullet_speed: 4.0, max_bullets: 6, points: 500,
+                behaviour: Behaviour::Boss, color: [0.5, 0.0, 0.5, 1.0]
+            }
+        }
+    }
+}
+
+impl Robot {
+    pub fn new(x: f64, y :f64, robot_type: RobotType,
+            action_state: EnimyState) -> Robot {
+
+        let stats = robot_type.stats();
+        let obj = GameObject {
+                                x: x, y: y,
+                                height: (stats.cells as f64)*HEIGHT_CELL_SIZE,
+                                width: (stats.cells as f64)*WIDTH_CELL_SIZE
+                            };
+
+        Robot {
+                object: obj, robot_type: robot_type, lives: stats.lives,
+                start_x: x, start_y: y, speed: stats.speed,
+                bullet_speed: stats.bullet_speed,
+                max_bullets: stats.max_bullets, bullets: vec![],
+                prepare_fire: Direct::NONE,
+                direct: Direct::NONE, next_direct: Direct::NONE,
+                action_state: action_state, hit_flash: 0, invulnerable: 0,
+                lured: 0, last_x: x, last_y: y
+        }
+    }
+
+    //body of robot in the middle of its cell, used for hitting
+    pub fn body(&self) -> GameObject {
+        let size = self.robot_type.stats().size;
+        GameObject {
+            x: self.object.x + (self.object.width - size)/2.0,
+            y: self.object.y + (self.object.height - size)/2.0,
+            width: size,
+            height: size
+        }
+    }
+
+    //position of body at the start of tick
+    pub fn last_body(&self) -> (f64, f64) {
+        let body = self.body();
+        (body.x + self.last_x - self.object.x,
+            body.y + self.last_y - self.object.y)
+    }
+
+    pub fn behaviour(&self) -> Behaviour {
+        self.robot_type.stats().behaviour
+    }
+
+    //damaged robot flashes for a while
+    pub fn flashing(&self) -> bool {
+        (self.hit_flash/4)%2 == 1
+    }
+
+    //color of robot, damaged robot flashes white
+    pub fn color(&self) -> [f32; 4] {
+        if self.flashing() {
+            [1.0, 1.0, 1.0, 1.0]
+        } else {
+            self.robot_type.stats().color
+        }
+    }
+
+    pub fn vulnerable(&self) -> bool {
+        self.invulnerable == 0
+    }
+
+    //robot is hidden on every other phase while it can't be damaged
+    pub fn blinking(&self) -> bool {
+        (self.invulnerable/8)%2 == 1
+    }
+
+    //check if robot can move over block of this type,
+    //only hero can't pass holes and only boss flies over walls
+    pub fn can_pass(&self, block_type: BlockType) -> bool {
+        match block_type {
+            BlockType::NODE | BlockType::SLIDE => true,
+            BlockType::HOLE => self.robot_type != RobotType::Hero,
+            BlockType::WALL => self.behaviour() == Behaviour::Boss
+        }
+    }
+
+    //accept command of controller
+    fn obey(&mut self, command: &Command) {
+        self.next_direct = command.direct;
+        self.prepare_fire = command.fire;
+        self.action_state = command.state;
+        if self.behaviour() == Behaviour::Boss {
+            self.speed = boss::speed(self);
+        }
+    }
+
+    //decrease lives, start flashing and become invulnerable for a while,
+    //return true if robot is destroyed
+    fn damage(&mut self) -> bool {
+        if self.lives > 0 {
+            self.lives -= 1;
+        }
+        self.hit_flash = HIT_FLASH_TICKS;
+        self.invulnerable = INVULNERABLE_TICKS;
+        self.lives <= 0
+    }
+}
+
+impl Difficulty {
+    pub fn hero_lives(&self) -> i64 {
+        match self {
+            Difficulty::Easy => 5,
+            Difficulty::Normal => RobotType::Hero.stats().lives,
+            Difficulty::Hard => 1
+        }
+    }
+}
+
+impl Block {
+    pub fn new(x: f64, y :f64, width: f64, height :f64,
+            block_type: BlockType) -> Block {
+        let obj = GameObject {
+                                x: x, y: y,
+                                width: width, height: height
+                            };
+        Block { object: obj, block_type: block_type}
+    }
+}
+
+impl Bullet {
+    pub fn new (x: f64, y :f64, width: f64, height :f64,
+            direct :Direct) -> Bullet {
+        let obj = GameObject {
+                                x: x, y: y,
+                                width: width, height: height
+                            };
+        Bullet { object: obj, direct: direct, last_x: x, last_y: y }
+    }
+
+    pub fn last(&self) -> (f64, f64) {
+        (self.last_x, self.last_y)
+    }
+}
+
+//time when bullet hits the first wall on its way during tick
+fn wall_time(bullet: &Bullet, blocks: &[Block]) -> Option<f64> {
+    let mut first: Option<f64> = None;
+    for block in blocks {
+        if block.block_type != BlockType::WALL {
+            continue;
+        }
+        let obj = &block.object;
+        if let Some(time) = sweep::hit_time(&bullet.object, bullet.last(),
+                                                        obj, (obj.x, obj.y)) {
+            first = Some(first.map_or(time, |first| first.min(time)));
+        }
+    }
+    first
+}
+
+//bullet flies to robot along its row or column and is near it
+fn aims_at(bullet: &Bullet, robot: &Robot) -> bool {
+    let (bullet_x, bullet_y) = center(&bullet.object);
+    let (robot_x, robot_y) = center(&robot.object);
+    let (along, across, size) = match bullet.direct {
+        Direct::LEFT => (bullet_x - robot_x, bullet_y - robot_y,
+                                                            WIDTH_CELL_SIZE),
+        Direct::RIGHT => (robot_x - bullet_x, bullet_y - robot_y,
+                                                            WIDTH_CELL_SIZE),
+        Direct::UP => (bullet_y - robot_y, bullet_x - robot_x,
+                                                            HEIGHT_CELL_SIZE),
+        Direct::DOWN => (robot_y - bullet_y, bullet_x - robot_x,
+                                                            HEIGHT_CELL_SIZE),
+        Direct::NONE => return false
+    };
+    along >= 0.0 && along <= size*LURE_CELLS && across.abs() < size/2.0
+}
+
+//remove bullets by their numbers
+fn remove_bullets(bullets: &mut Vec<Bullet>, fire_bullets: &BTreeSet<usize>) {
+    let mut counter = 0;
+    for bullet_num in fire_bullets {
+        bullets.remove(bullet_num-counter);
+        counter += 1;
+    }
+}
+
+impl Game {
+    pub fn new() -> Game {
+        let hero = Robot::new(0.0, 0.0, RobotType::Hero, EnimyState::Manual);
+        let blocks = vec![];
+        let enimies = vec![];
+        let free_bullets = vec![];
+
+        Game {
+                hero: hero, blocks: blocks, enimies: enimies,
+                free_bullets: free_bullets,
+                point_num: 0, crash_num: 0, scoring: Scoring::new(),
+                stats: GameStats::new(), ticks: 0,
+                level: 1, difficulty: Difficulty::Normal,
+                boss_pending: false, death_causes: vec![], events: vec![],
+                hero_respawn: 0,
+                squad_tactics: false, bullets_cancel: false,
+                enimies_controller: Box::new(ClassicAi::new()),
+                hero_controller: None,
+                rng: GameRng::new(rand::random()),
+                paused: false, game_over: false, game_win: false
+            }
+    }
+
+    //choose level for next create_level
+    pub fn set_level(&mut self, level: usize) {
+        self.level = level.min(LEVELS.len() - 1);
+    }
+
+    //width and height of landscape
+    pub fn map_size(&self) -> [f64; 2] {
+        let mut width: f64 = 0.0;
+        let mut height: f64 = 0.0;
+        for block in &self.blocks {
+            width = width.max(block.object.x + block.object.width);
+            height = height.max(block.object.y + block.object.height);
+        }
+        [width, height]
+    }
+
+    pub fn level_name(&self) -> &'static str {
+        LEVELS[self.level].name
+    }
+
+    //difficulty is applied by next create_level
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+    }
+
+    pub fn create_level(&mut self) {
+        self.paused = false;
+        self.game_over = false;
+        self.game_win = false;
+        self.crash_num = 0;
+        self.point_num = 0;
+        self.scoring = Scoring::new();
+        self.stats = GameStats::new();
+        self.ticks = 0;
+        self.boss_pending = true;
+        self.death_causes.clear();
+        self.hero_respawn = 0;
+
+        //clear object vectors
+        self.blocks.clear();
+        self.enimies.clear();
+        self.free_bullets.clear();
+
+        let level = &LEVELS[self.level];
+
+        //init hero
+        let hero_x = (level.hero.0 as f64)*WIDTH_CELL_SIZE;
+        let hero_y = (level.hero.1 as f64)*HEIGHT_CELL_SIZE;
+        let mut hero = Robot::new(hero_x, hero_y, RobotType::Hero,
+                                                        EnimyState::Manual);
+        hero.lives = self.difficulty.hero_lives();
+        self.hero = hero;
+
+        //init blocks, field of crossroads ends by odd column and row
+        //not nearer than three cells to the right and two cells to the bottom
+        //of the map
+        let last_column = level.columns - 1;
+        let last_row = level.rows - 1;
+        let odd_down = |cell: i64| if cell%2 == 1 { cell } else { cell - 1 };
+        let field_right = odd_down(last_column - 3);
+        let field_bottom = odd_down(last_row - 2);
+        for x_cell in 0..level.columns {
+            let x :f64 = (x_cell as f64)*WIDTH_CELL_SIZE;
+
+            for y_cell in 0..level.rows {
+                let y :f64 = (y_cell as f64)*HEIGHT_CELL_SIZE;
+
+                let mut block_type = match x_cell%2 {
+                    0 => match y_cell%2 {
+                        0 => BlockType::WALL,
+                        _ => BlockType::HOLE,
+                    },
+                    _ => BlockType::HOLE
+                };
+
+                block_type = if (x_cell >= 3) && (y_cell >= 3) &&
+                    (x_cell <= field_right) && (y_cell <= field_bottom) {
+                    match block_type {
+                        BlockType::HOLE =>
+                                if (x_cell%2 == 0)||(y_cell%2 == 0) {
+                                    BlockType::SLIDE
+                                } else {
+                                    BlockType::NODE
+                                },
+                        _ => block_type
+                    }
+                } else {
+                    block_type
+                };
+
+                if x_cell == 0 || x_cell == last_column ||
+                                        y_cell == 0 || y_cell == last_row {
+                    block_type = BlockType::WALL;
+                }
+
+                let block = Block::new(x, y,
+                        WIDTH_CELL_SIZE, HEIGHT_CELL_SIZE,
+                        block_type);
+                self.blocks.push(block);
+            }
+        }
+
+        //init enemies of current level
+        let spawns = level.spawns;
+        for &(x_cell, y_cell, robot_type, action_state) in spawns.iter() {
+            let x :f64 = (x_cell as f64)*WIDTH_CELL_SIZE;
+            let y :f64 = (y_cell as f64)*HEIGHT_CELL_SIZE;
+            let enimy = Robot::new(x, y, robot_type, action_state);
+            self.enimies.push(enimy);
+        }
+    }
+
+    fn move_robots(&mut self) {
+
+        let robot_move = |robot:&mut Robot, blocks: &Vec<Block>, hero: bool| {
+
+            //------------------------move logic-----------
+            let next_direct_hit_side = match robot.next_direct {
+                Direct::LEFT => HitTestType::LEFT,
+                Direct::RIGHT => HitTestType::RIGHT,
+                Direct::UP => HitTestType::UP,
+                Direct::DOWN => HitTestType::DOWN,
+                Direct::NONE => HitTestType::INNER,
+                //_ => panic!("Incorrect direction for robot")
+            };
+
+            let direct_hit_side = match robot.direct {
+                Direct::LEFT => HitTestType::LEFT,
+                Direct::RIGHT => HitTestType::RIGHT,
+                Direct::UP => HitTestType::UP,
+                Direct::DOWN => HitTestType::DOWN,
+                Direct::NONE => HitTestType::INNER,
+                //_ => panic!("Incorrect direction for robot")
+            };
+
+            //check if cell slide
+            let mut slided = false;
+            for block in blocks {
+                let intersect = block.object.rectangle_hit_test(&robot.object,
+                                                            HitTestType::INNER);
+                let slide_block = match block.block_type {
+                    BlockType::SLIDE => true,
+                    _ => false
+                };
+
+                if slide_block && intersect {
+                    slided = true;
+                    break;
+                }
+            }
+            if !hero {
+                slided = false;
+            }
+
+            let opposite_direct = match robot.direct {
+                Direct::LEFT => Direct::RIGHT,
+                Direct::RIGHT => Direct::LEFT,
+                Direct::UP => Direct::DOWN,
+                Direct::DOWN => Direct::UP,
+                _ => Direct::NONE
+            };
+
+            //if can change direction, check if direct passable
+            //let mut stoped = false;
+            let mut blocked_next_direct = false;
+            let mut blocked_direct = false;
+            for block in blocks {
+
+                let next_intersect =
+                    block.object.rectangle_hit_test(&robot.object,
+                                                        next_direct_hit_side);
+                let current_intersect = block.object.rectangle_hit_test(
+                                        &robot.object, direct_hit_side);
+
+                if !robot.can_pass(block.block_type) {
+                    if next_intersect {
+                        blocked_next_direct = true;
+                    }
+                    if current_intersect {
+                        blocked_direct = true;
+                    }
+                }
+
+            }
+
+            let mut new_direct :Direct = robot.direct;
+            let mut new_next_direct :Direct = robot.next_direct;
+            if !slided || (robot.next_direct == opposite_direct) {
+                new_direct = new_next_direct;
+                if blocked_next_direct {
+                    new_direct = Direct::NONE;
+                    new_next_direct = Direct::NONE;
+                }
+            }
+
+            if slided && blocked_direct {
+                new_direct = opposite_direct;
+                new_next_direct = Direct::NONE;
+            }
+
+            //access new changing
+            let speed = robot.speed;
+            robot.direct = new_direct;
+            robot.next_direct = new_next_direct;
+            match new_direct {
+                Direct::LEFT => robot.object.x -= speed,
+                Direct::RIGHT => robot.object.x += speed,
+                Direct::UP => robot.object.y -= speed,
+                Direct::DOWN => robot.object.y += speed,
+                Direct::NONE => {},
+                //_ => panic!("Invalid direction for hero", )
+            }
+        };
+
+        let blocks = &self.blocks;
+        if self.hero_respawn == 0 {
+            robot_move(&mut self.hero, blocks, true);
+        }
+        for enimy in &mut self.enimies {
+            robot_move(enimy, blocks, false);
+        }
+
+    }
+
+    fn create_bullets(&mut self) {
+
+        let create_bullet = |robot:&mut Robot, blocks: &Vec<Block>| {
+
+            let check_rate = robot.bullets.len() < robot.max_bullets;
+            let check_command = match robot.prepare_fire {
+                Direct::NONE => false,
+                _ => true
+            };
+
+            let hit_side = match robot.prepare_fire {
+                Direct::LEFT => HitTestType::LEFT,
+                Direct::RIGHT => HitTestType::RIGHT,
+                Direct::UP => HitTestType::UP,
+                Direct::DOWN => HitTestType::DOWN,
+                Direct::NONE => HitTestType::INNER,
+                //_ => panic!("Incorrect direction for robot")
+            };
+
+            let mut check_shootable = true;
+            for block in blocks {
+                let intersect = block.object.rectangle_hit_test(&robot.object,
+                                                            hit_side);
+                let shootable = match block.block_type {
+                    BlockType::WALL => false,
+                    _ => true
+                };
+                if !shootable && intersect {
+                    check_shootable = false;
+                }
+            }
+
+            if check_rate && check_command && check_shootable {
+
+                //let bullet = Bullet::new();
+                let bullet_width :f64 = 8.0;
+                let bullet_height :f64 = 8.0;
+
+                let bullet_x = match robot.prepare_fire {
+                    Direct::LEFT => robot.object.x - bullet_width,
+                    Direct::RIGHT =>
+                        robot.object.x + robot.object.width + bullet_width,
+                    Direct::UP =>
+                        robot.object.x + robot.object.width/2.0
+                                                        - bullet_width/2.0,
+                    Direct::DOWN =>
+                        robot.object.x + robot.object.width/2.0
+                                                        - bullet_width/2.0,
+                    _ => panic!("Invalid value for preparing shoot")
+                };
+
+                let bullet_y = match robot.prepare_fire {
+                    Direct::LEFT =>
+                        robot.object.y + robot.object.height/2.0
+                                                        - bullet_height/2.0,
+                    Direct::RIGHT =>
+                        robot.object.y + robot.object.height/2.0
+                                                        - bullet_height/2.0,
+                    Direct::UP => robot.object.y - bullet_height,
+                    Direct::DOWN =>
+                        robot.object.y + robot.object.height + bullet_height,
+                    _ => panic!("Invalid value for preparing shoot")
+                };
+
+                let bullet = Bullet::new(bullet_x, bullet_y,
+                                        bullet_width, bullet_height,
+                                        robot.prepare_fire);
+                robot.bullets.push(bullet);
+
+                robot.prepare_fire = Direct::NONE;
+            }
+
+        };
+
+        let blocks = &self.blocks;
+        let hero_bullets = self.hero.bullets.len();
+        let enimy_bullets: Vec<usize> = self.enimies.iter()
+                                    .map(|enimy| enimy.bullets.len()).collect();
+        if self.hero_respawn == 0 {
+            create_bullet(&mut self.hero, blocks);
+        }
+
+        for enimy in &mut self.enimies {
+            //gun of boss fires several bullets at once
+            if enimy.behaviour() == Behaviour::Boss {
+                boss::fire(enimy);
+            } else {
+                create_bullet(enimy, blocks);
+            }
+        }
+
+        //new bullets are at the end of lists
+        let events = &mut self.events;
+        let hero = &self.hero;
+        let shooters = std::iter::once((hero, hero_bullets))
+                        .chain(self.enimies.iter().zip(enimy_bullets));
+        for (robot, bullets_before) in shooters {
+            for bullet in &robot.bullets[bullets_before..] {
+                let (x, y) = center(&bullet.object);
+                events.push(GameEvent::ShotFired {
+                    robot_type: robot.robot_type,
+                    x: x, y: y,
+                    direct: bullet.direct
+                });
+            }
+        }
+    }
+
+    fn move_bullets(&mut self) {
+
+        let move_bullet = |bullets: &mut Vec<Bullet>, bullet_speed: f64| {
+            for bullet in bullets {
+                let add_x = match bullet.direct {
+                    Direct::LEFT => -bullet_speed,
+                    Direct::RIGHT => bullet_speed,
+                    _ => 0.0
+                };
+
+                let add_y = match bullet.direct {
+                    Direct::DOWN => bullet_speed,
+                    Direct::UP => -bullet_speed,
+                    _ => 0.0
+                };
+
+                bullet.object.x += add_x;
+                bullet.object.y += add_y;
+            }
+        };
+
+        move_bullet(&mut self.hero.bullets, self.hero.bullet_speed);
+        for enimy in &mut self.enimies {
+            move_bullet(&mut enimy.bullets, enimy.bullet_speed);
+        }
+        move_bullet(&mut self.free_bullets, self.hero.bullet_speed);
+
+    }
+
+    fn collision_bullets(&mut self) {
+
+        let [map_width, map_height] = self.map_size();
+        let field_out = |bullets :&Vec<Bullet>| -> Vec<usize> {
+            //check bullets that out of range
+            let mut fire_bullets = vec![];
+            for (bullet_num, bullet) in bullets.iter().enumerate() {
+
+                let check_left = bullet.object.x < 0.0;
+                let check_right = bullet.object.x > map_width;
+                let check_top = bullet.object.y < 0.0;
+                let check_bottom = bullet.object.y > map_height;
+                if check_left || check_right || check_top || check_bottom {
+                    fire_bullets.push(bullet_num)
+                }
+            }
+            fire_bullets
+        };
+        //bullet hits robot on its way during tick if there is no wall
+        //between them
+        let robot_collision = |bullets :&Vec<Bullet>, goal_robot: &Robot,
+                                        blocks: &Vec<Block>| -> Vec<usize> {
+
+            let mut fire_bullets = vec![];
+            let body = goal_robot.body();
+            let last_body = goal_robot.last_body();
+            for (bullet_num, bullet) in bullets.iter().enumerate() {
+                let hit = sweep::hit_time(&bullet.object, bullet.last(),
+                                                            &body, last_body);
+                if let Some(time) = hit {
+                    if wall_time(bullet, blocks).map_or(true,
+                                                    |wall| time < wall) {
+                        fire_bullets.push(bullet_num);
+                        break;
+                    }
+                }
+            }
+            fire_bullets
+        };
+        //bullets hitting walls with centers at the point of hit
+        let block_collision = |bullets :&Vec<Bullet>, blocks: &Vec<Block>|
+                                            -> Vec<(usize, (f64, f64))> {
+
+            let mut fire_bullets = vec![];
+            for (bullet_num, bullet) in bullets.iter().enumerate() {
+                if let Some(time) = wall_time(bullet, blocks) {
+                    let point = sweep::center_at(&bullet.object,
+                                                        bullet.last(), time);
+                    fire_bullets.push((bullet_num, point));
+                }
+            }
+            fire_bullets
+        };
+
+        //bullets fly through hero while he is away or invulnerable
+        let hero_hittable = self.hero_on_field() && self.hero.vulnerable();
+
+        //check hero bullets
+        let mut fire_hero_bullets :BTreeSet<usize> = BTreeSet::new();
+        let mut fire_enemies :BTreeSet<usize> = BTreeSet::new();
+        let mut hero_death = None;
+
+        for bullet_num in field_out(&self.hero.bullets) {
+            fire_hero_bullets.insert(bullet_num);
+        }
+        for (bullet_num, (x, y)) in block_collision(&self.hero.bullets,
+                                                                &self.blocks) {
+            fire_hero_bullets.insert(bullet_num);
+            self.events.push(GameEvent::BulletHitWall { x: x, y: y });
+        }
+
+        for (enimy_num, enimy) in self.enimies.iter_mut().enumerate() {
+            let enimy_fire_bullets = robot_collision(&self.hero.bullets, enimy,
+                                                                &self.blocks);
+            if enimy_fire_bullets.len() > 0 {
+                for bullet_num in enimy_fire_bullets {
+                    fire_hero_bullets.insert(bullet_num);
+                }
+                //enemy is removed only when it loses all lives
+                if enimy.vulnerable() {
+                    self.scoring.hit();
+                    let (x, y) = center(&enimy.object);
+                    if enimy.damage() {
+                        self.point_num += 1;
+                        self.scoring.kill(enimy.robot_type);
+                        fire_enemies.insert(enimy_num);
+                        self.events.push(GameEvent::EnemyKilled {
+                            robot_type: enimy.robot_type, x: x, y: y,
+                            by: Damager::HeroBullet,
+                            state: enimy.action_state
+                        });
+                    } else {
+                        self.events.push(GameEvent::EnemyHit {
+                            robot_type: enimy.robot_type, x: x, y: y,
+                            by: Damager::HeroBullet
+                        });
+                    }
+                }
+            }
+        }
+        //self fired
+        let self_fire_bullets = if hero_hittable {
+            robot_collision(&self.hero.bullets, &self.hero, &self.blocks)
+        } else {
+            vec![]
+        };
+        if self_fire_bullets.len() > 0 {
+            for bullet_num in self_fire_bullets {
+                fire_hero_bullets.insert(bullet_num);
+            }
+            hero_death = Some(DeathCause::OwnBullet);
+        }
+
+        //remove hero bullets
+        let hero_bullets = &mut self.hero.bullets;
+        let mut counter = 0;
+        for bullet_num in fire_hero_bullets {
+             //hero_bullets.remove(bullet_num);
+             hero_bullets.remove(bullet_num-counter);
+             counter += 1;
+        }
+
+        //check free bullets
+        let mut fire_free_bullets :BTreeSet<usize> = BTreeSet::new();
+        for bullet_num in field_out(&self.free_bullets) {
+            fire_free_bullets.insert(bullet_num);
+        }
+        for (bullet_num, (x, y)) in block_collision(&self.free_bullets,
+                                                                &self.blocks) {
+            fire_free_bullets.insert(bullet_num);
+            self.events.push(GameEvent::BulletHitWall { x: x, y: y });
+        }
+
+        //hero fired
+        let hero_fire_bullets = if hero_hittable {
+            robot_collision(&self.free_bullets, &self.hero, &self.blocks)
+        } else {
+            vec![]
+        };
+        if hero_fire_bullets.len() > 0 {
+            for bullet_num in hero_fire_bullets {
+                fire_free_bullets.insert(bullet_num);
+            }
+            hero_death = Some(DeathCause::FreeBullet);
+        }
+
+        //remove free bullets
+        let free_bullets = &mut self.free_bullets;
+        let mut counter = 0;
+        for bullet_num in fire_free_bullets {
+             //hero_bullets.remove(bullet_num);
+             free_bullets.remove(bullet_num-counter);
+             counter += 1;
+        }
+
+        let mut fire_enimy_bullets :Vec<BTreeSet<usize>> = vec![];
+        //check every enimy bullets
+        for (shooter_num, shooter_enemy) in self.enimies.iter().enumerate() {
+
+            fire_enimy_bullets.push(BTreeSet::new());
+            let enimy_bullet_set = &mut fire_enimy_bullets[shooter_num];
+
+            for bullet_num in field_out(&shooter_enemy.bullets) {
+                enimy_bullet_set.insert(bullet_num);
+            }
+
+            for (bullet_num, (x, y)) in block_collision(&shooter_enemy.bullets,
+                                                            &self.blocks) {
+                enimy_bullet_set.insert(bullet_num);
+                self.events.push(GameEvent::BulletHitWall { x: x, y: y });
+            }
+
+            // for (enimy_num, enimy) in self.enimies.iter().enumerate() {
+            //     let local_enimy_fire_bullets =
+            //                         robot_collision(shooter_enemy, enimy);
+            //     if local_enimy_fire_bullets.len() > 0 {
+            //         for bullet_num in local_enimy_fire_bullets {
+            //             enimy_bullet_set.insert(bullet_num);
+            //         }
+            //         fire_enemies.insert(enimy_num);
+            //     }
+            // }
+
+            //hero fired
+            let local_enimy_fire_bullets = if hero_hittable {
+                robot_collision(&shooter_enemy.bullets, &self.hero,
+                                                                &self.blocks)
+            } else {
+                vec![]
+            };
+            if local_enimy_fire_bullets.len() > 0 {
+                for bullet_num in local_enimy_fire_bullets {
+                    enimy_bullet_set.insert(bullet_num);
+                }
+                hero_death = Some(DeathCause::EnemyBullet);
+            }
+
+        }
+
+        //remove enimy bullets
+        let mut shooter_num = 0;
+        //println!("START REMOVE bullets IN collision_bullets");
+        for shooter_fired_bullets in &fire_enimy_bullets {
+            let enimy_bullets = &mut self.enimies[shooter_num].bullets;
+            let mut counter = 0;
+            for bullet_num in shooter_fired_bullets {
+                 //hero_bullets.remove(bullet_num);
+                 enimy_bullets.remove(bullet_num-counter);
+                 counter += 1;
+            }
+            shooter_num += 1;
+        }
+        //println!("END REMOVE bullets IN collision_bullets");
+
+        //remove enemies
+        //let enimies = &mut ;
+        let mut counter = 0;
+        //println!("START REMOVE enimies IN collision_bullets");
+        for enimy_num in fire_enemies {
+            let index = enimy_num-counter;
+            self.free_bullets.append(&mut self.enimies[index].bullets);
+            self.enimies.remove(index);
+            counter += 1;
+        }
+        //println!("END REMOVE enimies IN collision_bullets");
+
+        //remove hero
+        if let Some(cause) = hero_death {
+            if self.hero.lives > 0 {
+                self.hero_hit(cause);
+            }
+        }
+
+    }
+
+    //bullet of hero and bullet of enemy or destroyed robot destroy each other
+    //when they meet before hitting walls
+    fn cancel_bullets(&mut self) {
+        let blocks = &self.blocks;
+        let mut fire_hero_bullets :BTreeSet<usize> = BTreeSet::new();
+        let mut fire_free_bullets :BTreeSet<usize> = BTreeSet::new();
+        let mut fire_enimy_bullets :Vec<BTreeSet<usize>> = self.enimies.iter()
+                                        .map(|_| BTreeSet::new()).collect();
+
+        for (hero_num, hero_bullet) in self.hero.bullets.iter().enumerate() {
+            let hero_wall = wall_time(hero_bullet, blocks);
+
+            //the first met bullet: time, number of shooter (None - free
+            //bullet) and number of bullet
+            let mut first: Option<(f64, Option<usize>, usize)> = None;
+            let free_bullets = self.free_bullets.iter().enumerate()
+                    .map(|(bullet_num, bullet)| (None, bullet_num, bullet));
+            let enimy_bullets = self.enimies.iter().enumerate()
+                .flat_map(|(enimy_num, enimy)| enimy.bullets.iter().enumerate()
+                    .map(move |(bullet_num, bullet)| (Some(enimy_num),
+                                                        bullet_num, bullet)));
+            let hostile_bullets = free_bullets.chain(enimy_bullets);
+            for (shooter, bullet_num, bullet) in hostile_bullets {
+                let fired = match shooter {
+                    Some(enimy_num) =>
+                        fire_enimy_bullets[enimy_num].contains(&bullet_num),
+                    None => fire_free_bullets.contains(&bullet_num)
+                };
+                if fired {
+                    continue;
+                }
+                let time = match sweep::hit_time(&hero_bullet.object,
+                                hero_bullet.last(), &bullet.object,
+                                                            bullet.last()) {
+                    Some(time) => time,
+                    None => continue
+                };
+                let before_walls = hero_wall.map_or(true, |wall| time < wall) &&
+                        wall_time(bullet, blocks).map_or(true,
+                                                        |wall| time < wall);
+                let earlier = first.map_or(true,
+                                        |(first_time, _, _)| time < first_time);
+                if before_walls && earlier {
+                    first = Some((time, shooter, bullet_num));
+                }
+            }
+
+            if let Some((time, shooter, bullet_num)) = first {
+                fire_hero_bullets.insert(hero_num);
+                let bullet = match shooter {
+                    Some(enimy_num) => {
+                        fire_enimy_bullets[enimy_num].insert(bullet_num);
+                        &self.enimies[enimy_num].bullets[bullet_num]
+                    },
+                    None => {
+                        fire_free_bullets.insert(bullet_num);
+                        &self.free_bullets[bullet_num]
+                    }
+                };
+                let (hero_x, hero_y) = sweep::center_at(&hero_bullet.object,
+                                                    hero_bullet.last(), time);
+                let (x, y) = sweep::center_at(&bullet.object, bullet.last(),
+                                                                        time);
+                self.events.push(GameEvent::BulletsCancelled {
+                    x: (hero_x + x)/2.0,
+                    y: (hero_y + y)/2.0
+                });
+            }
+        }
+
+        remove_bullets(&mut self.hero.bullets, &fire_hero_bullets);
+        remove_bullets(&mut self.free_bullets, &fire_free_bullets);
+        for (enimy, fire_bullets) in self.enimies.iter_mut()
+                                            .zip(&fire_enimy_bullets) {
+            remove_bullets(&mut enimy.bullets, fire_bullets);
+        }
+    }
+
+    fn collision_robots(&mut self) {
+
+        let mut hero_death = None;
+        let mut enimies_hit :BTreeSet<usize> = BTreeSet::new();
+        let mut hero_touches :BTreeSet<usize> = BTreeSet::new();
+        let mut enimies_die :BTreeSet<usize> = BTreeSet::new();
+        //enemies crashed with enemy lured by hero
+        let mut enimies_lured :BTreeSet<usize> = BTreeSet::new();
+
+        let hero_body = self.hero.body();
+        let hero_last = self.hero.last_body();
+        //enemies pass through hero while he is away or invulnerable
+        let hero_hittable = self.hero_on_field() && self.hero.vulnerable();
+        for (enimy_goal_num, enimy_goal) in self.enimies.iter().enumerate() {
+            let goal_body = enimy_goal.body();
+            let goal_last = enimy_goal.last_body();
+            for (enimy_num, enimy) in self.enimies.iter().enumerate() {
+
+                if enimy_goal_num == enimy_num {
+                    continue;
+                }
+
+                //robots touched each other at any moment of tick
+                if sweep::hit_time(&enimy.body(), enimy.last_body(),
+                                        &goal_body, goal_last).is_some() {
+                    enimies_hit.insert(enimy_goal_num);
+                    if enimy_goal.lured > 0 || enimy.lured > 0 {
+                        enimies_lured.insert(enimy_goal_num);
+                    }
+
+                    //every pair is reported once
+                    let damaged = enimy_goal.vulnerable() || enimy.vulnerable();
+                    if enimy_goal_num < enimy_num && damaged {
+                        let (goal_x, goal_y) = center(&enimy_goal.object);
+                        let (x, y) = center(&enimy.object);
+                        self.events.push(GameEvent::EnemiesCrashed {
+                            first: enimy_goal.robot_type,
+                            second: enimy.robot_type,
+                            x: (goal_x + x)/2.0,
+                            y: (goal_y + y)/2.0
+                        });
+                    }
+                }
+            }
+
+            if hero_hittable && sweep::hit_time(&hero_body, hero_last,
+                                        &goal_body, goal_last).is_some() {
+                enimies_hit.insert(enimy_goal_num);
+                hero_touches.insert(enimy_goal_num);
+            }
+
+        }
+
+        //crash is caused by hero only while he is on the field
+        let hero_present = self.hero_on_field();
+
+        //contact damages enemy like bullet, invulnerable enemy is not
+        //damaged, but it still damages hero
+        for enimy_num in enimies_hit {
+            let enimy = &mut self.enimies[enimy_num];
+            if hero_touches.contains(&enimy_num) {
+                hero_death = Some(DeathCause::Contact);
+            }
+            if !enimy.vulnerable() {
+                continue;
+            }
+            let (x, y) = center(&enimy.object);
+            let by = if hero_touches.contains(&enimy_num) {
+                Damager::Hero
+            } else {
+                Damager::Collision
+            };
+            if enimy.damage() {
+                enimies_die.insert(enimy_num);
+                self.crash_num += 1;
+                if hero_present && enimies_lured.contains(&enimy_num) &&
+                                        !hero_touches.contains(&enimy_num) {
+                    self.scoring.crash(enimy.robot_type);
+                }
+                self.events.push(GameEvent::EnemyKilled {
+                    robot_type: enimy.robot_type, x: x, y: y,
+                    by: by, state: enimy.action_state
+                });
+            } else {
+                self.events.push(GameEvent::EnemyHit {
+                    robot_type: enimy.robot_type, x: x, y: y, by: by
+                });
+            }
+        }
+
+        //remove enemies
+        let mut counter = 0;
+        for enimy_num in enimies_die {
+            let index = enimy_num-counter;
+            self.free_bullets.append(&mut self.enimies[index].bullets);
+            self.enimies.remove(enimy_num-counter);
+            counter += 1;
+        }
+
+        //remove hero
+        if let Some(cause) = hero_death {
+            if self.hero.lives > 0 {
+                self.hero_hit(cause);
+            }
+        }
+
+    }
+
+    //hero loses life and leaves the field until respawn
+    fn hero_hit(&mut self, cause: DeathCause) {
+        self.hero.lives -= 1;
+        self.death_causes.push(cause);
+        self.scoring.break_combo();
+        let (x, y) = center(&self.hero.object);
+        self.events.push(GameEvent::HeroHit {
+            cause: cause, x: x, y: y,
+            lives: self.hero.lives
+        });
+
+        let hero = &mut self.hero;
+        hero.direct = Direct::NONE;
+        hero.next_direct = Direct::NONE;
+        hero.prepare_fire = Direct::NONE;
+        if hero.lives > 0 {
+            self.hero_respawn = RESPAWN_DELAY_TICKS;
+        }
+    }
+
+    //hero is on the field and can act
+    pub fn hero_on_field(&self) -> bool {
+        self.hero_respawn == 0 && self.hero.lives > 0
+    }
+
+    //count down time of respawn, hero appears at safe point
+    //and can't be damaged for a while
+    fn respawn_hero(&mut self) {
+        if self.hero_respawn == 0 {
+            return;
+        }
+        self.hero_respawn -= 1;
+        if self.hero_respawn > 0 {
+            return;
+        }
+
+        let (x, y) = self.safe_spawn();
+        let hero = &mut self.hero;
+        hero.object.x = x;
+        hero.object.y = y;
+        hero.direct = Direct::NONE;
+        hero.next_direct = Direct::NONE;
+        hero.prepare_fire = Direct::NONE;
+        hero.invulnerable = RESPAWN_INVULNERABLE_TICKS;
+        let (center_x, center_y) = center(&hero.object);
+        self.events.push(GameEvent::HeroRespawned { x: center_x,
+                                                    y: center_y });
+    }
+
+    //crossroad nearest to start of hero which is far from enemies
+    //and bullets, start of hero if there is no such one
+    fn safe_spawn(&self) -> (f64, f64) {
+        let start = (self.hero.start_x, self.hero.start_y);
+        let distance = |a: (f64, f64), b: (f64, f64)| {
+            ((a.0 - b.0)/WIDTH_CELL_SIZE).abs()
+                .max(((a.1 - b.1)/HEIGHT_CELL_SIZE).abs())
+        };
+
+        let enimy_bullets = self.enimies.iter()
+                                .flat_map(|enimy| enimy.bullets.iter());
+        let bullets: Vec<(f64, f64)> = self.free_bullets.iter()
+                                .chain(enimy_bullets)
+                                .map(|bullet| center(&bullet.object))
+                                .collect();
+        let enimies: Vec<(f64, f64)> = self.enimies.iter()
+                                .map(|enimy| center(&enimy.body()))
+                                .collect();
+        let safe = |point: (f64, f64)| {
+            let point_center = (point.0 + WIDTH_CELL_SIZE/2.0,
+                                point.1 + HEIGHT_CELL_SIZE/2.0);
+            enimies.iter().all(|&enimy| {
+                distance(enimy, point_center) >= SAFE_ENIMY_CELLS
+            }) && bullets.iter().all(|&bullet| {
+                distance(bullet, point_center) >= SAFE_BULLET_CELLS
+            })
+        };
+
+        let mut points: Vec<(f64, f64)> = self.blocks.iter()
+                        .filter(|block| block.block_type == BlockType::NODE)
+                        .map(|block| (block.object.x, block.object.y))
+                        .collect();
+        points.sort_by(|&a, &b| {
+            distance(a, start).partial_cmp(&distance(b, start))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        points.into_iter()
+            .find(|&point| safe(point))
+            .unwrap_or(start)
+    }
+
+    //bullets of hero and enemies destroy each other when they meet
+    pub fn set_bullets_cancel(&mut self, bullets_cancel: bool) {
+        self.bullets_cancel = bullets_cancel;
+    }
+
+    //enemies are controlled as a squad or act independently
+    pub fn set_squad_tactics(&mut self, squad_tactics: bool) {
+        self.squad_tactics = squad_tactics;
+        self.enimies_controller = if self.squad_tactics {
+            Box::new(SquadAi::new(4))
+        } else {
+            Box::new(ClassicAi::new())
+        };
+    }
+
+    //restart random decisions from seed to repeat the game
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = GameRng::new(seed);
+    }
+
+    //drive enemies by another controller
+    pub fn set_enimies_controller(&mut self, controller: Box<dyn Controller>) {
+        self.enimies_controller = controller;
+    }
+
+    //drive hero by controller, None returns manual control
+    pub fn set_hero_controller(&mut self,
+                                controller: Option<Box<dyn Controller>>) {
+        self.hero_controller = controller;
+    }
+
+    pub fn view(&self) -> GameView<'_> {
+        GameView {
+            hero: &self.hero,
+            enimies: &self.enimies,
+            blocks: &self.blocks,
+            map_size: self.map_size(),
+            free_bullets: &self.free_bullets,
+            point_num: self.point_num,
+            crash_num: self.crash_num,
+            score: self.score()
+        }
+    }
+
+    pub fn score(&self) -> i64 {
+        self.scoring.total()
+    }
+
+    pub fn scoring(&self) -> &Scoring {
+        &self.scoring
+    }
+
+    pub fn stats(&self) -> &GameStats {
+        &self.stats
+    }
+
+    //statistics of the step from its events and state of hero
+    fn collect_stats(&mut self) {
+        self.stats.ticks += 1;
+        if self.hero_on_field() {
+            let hero = &self.hero.object;
+            let sliding = self.blocks.iter().any(|block|
+                block.block_type == BlockType::SLIDE &&
+                    block.object.rectangle_hit_test(hero, HitTestType::INNER));
+            let standing = self.hero.object.x == self.hero.last_x &&
+                                    self.hero.object.y == self.hero.last_y;
+            if standing {
+                self.stats.standing_ticks += 1;
+            } else if sliding {
+                self.stats.sliding_ticks += 1;
+            } else {
+                self.stats.moving_ticks += 1;
+            }
+        }
+        for event in &self.events {
+            self.stats.event(event);
+        }
+    }
+
+    //positions of robots and bullets at the start of tick
+    fn remember_positions(&mut self) {
+        let hero = &mut self.hero;
+        for robot in self.enimies.iter_mut().chain(std::iter::once(hero)) {
+            robot.last_x = robot.object.x;
+            robot.last_y = robot.object.y;
+            for bullet in &mut robot.bullets {
+                bullet.last_x = bullet.object.x;
+                bullet.last_y = bullet.object.y;
+            }
+        }
+        for bullet in &mut self.free_bullets {
+            bullet.last_x = bullet.object.x;
+            bullet.last_y = bullet.object.y;
+        }
+    }
+
+    //count down timers of robots and combo
+    fn update_timers(&mut self) {
+        self.scoring.tick();
+        let hero = &mut self.hero;
+        for robot in self.enimies.iter_mut().chain(std::iter::once(hero)) {
+            if robot.hit_flash > 0 {
+                robot.hit_flash -= 1;
+            }
+            if robot.invulnerable > 0 {
+                robot.invulnerable -= 1;
+            }
+            if robot.lured > 0 {
+                robot.lured -= 1;
+            }
+        }
+    }
+
+    //boss appears in the patrol lane over the field
+    fn spawn_boss(&mut self) {
+        let x = ((LEVELS[self.level].columns/2 - 1) as f64)*WIDTH_CELL_SIZE;
+        let y = boss::PATROL_ROW*HEIGHT_CELL_SIZE;
+        let boss = Robot::new(x, y, RobotType::Boss, EnimyState::BossPatrol);
+        let (center_x, center_y) = center(&boss.object);
+        self.events.push(GameEvent::BossAppeared { x: center_x, y: center_y });
+        self.enimies.push(boss);
+        self.boss_pending = false;
+    }
+
+    fn logic(&mut self) {
+        self.update_timers();
+        self.respawn_hero();
+        self.remember_positions();
+        self.move_robots();
+        self.create_bullets();
+        self.move_bullets();
+        if self.bullets_cancel {
+            self.cancel_bullets();
+        }
+        self.collision_bullets();
+        self.collision_robots();
+
+        if self.enimies.is_empty() && self.boss_pending {
+            self.spawn_boss();
+        }
+    }
+
+    //ask controllers for commands and pass them to robots
+    fn decisions(&mut self) {
+        //controllers and generator are taken out of the game
+        //while they look at its view, then they are put back
+        let mut enimies_controller = std::mem::replace(
+                    &mut self.enimies_controller, Box::new(ClassicAi::new()));
+        let mut hero_controller = self.hero_controller.take();
+        let mut rng = self.rng.clone();
+
+        let (enimies_commands, hero_commands) = {
+            let view = self.view();
+            let enimies_commands = enimies_controller.decide(&view,
+                                                    &self.enimies, &mut rng);
+            let hero_commands = match &mut hero_controller {
+                Some(controller) => controller.decide(&view,
+                                std::slice::from_ref(&self.hero), &mut rng),
+                None => vec![]
+            };
+            (enimies_commands, hero_commands)
+        };
+
+        self.enimies_controller = enimies_controller;
+        self.hero_controller = hero_controller;
+        self.rng = rng;
+
+        let hero_bullets = &self.hero.bullets;
+        for (index, (enimy, command)) in self.enimies.iter_mut()
+                                    .zip(enimies_commands.iter()).enumerate() {
+            let from = enimy.action_state;
+            let from_direct = enimy.next_direct;
+            enimy.obey(command);
+            //enemy chasing hero or turning away from his bullet is lured
+            let chasing = match enimy.action_state {
+                EnimyState::Attack | EnimyState::BossCharge => true,
+                _ => false
+            };
+            let dodging = enimy.next_direct != from_direct &&
+                    hero_bullets.iter().any(|bullet| aims_at(bullet, enimy));
+            if chasing || dodging {
+                enimy.lured = LURE_TICKS;
+            }
+            if enimy.action_state != from {
+                self.events.push(GameEvent::EnemyStateChanged {
+                    index: index,
+                    robot_type: enimy.robot_type,
+                    from: from,
+                    to: enimy.action_state
+                });
+            }
+        }
+        if let Some(command) = hero_commands.first() {
+            self.hero.obey(command);
+        }
+    }
+
+    //one step of the game, nothing happens while it is paused or over
+    pub fn tick(&mut self) {
+        if self.paused {
+            self.events.clear();
+            return;
+        }
+        self.step();
+    }
+
+    //one step of the game even if it is paused, nothing happens if it's over
+    pub fn step(&mut self) {
+        self.events.clear();
+        if self.game_over {
+            return;
+        }
+
+        self.decisions();
+        self.logic();
+        self.ticks += 1;
+
+        if self.hero.lives <= 0 {
+            self.game_over = true;
+            self.game_win = false;
+            self.events.push(GameEvent::LevelFailed);
+        } else if self.enimies.len() <= 0 {
+            self.game_over = true;
+            self.game_win = true;
+            let seconds = self.ticks/(TICKS_PER_SECOND as u64);
+            self.scoring.finish(seconds, LEVELS[self.level].par_seconds,
+                                                            self.hero.lives);
+            self.events.push(GameEvent::LevelWon);
+        }
+        self.collect_stats();
+    }
+
+    //events which happened during the last step
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+}
+
+impl App {
+
+    pub fn new(opengl: OpenGL) -> App {
+        // Create a new game, it's started from title screen
+        let game = Game::new();
+
+        let glyphs = GlyphCache::from_bytes(FONT, (), TextureSettings::new())
+                                            .expect("Can't load bundled font");
+
+        let settings = Settings::default();
+        let (scores, scores_error) = HighScores::load_default();
+        let (lifetime, lifetime_error) = LifetimeStats::load_default();
+        let mut mixer = Mixer::with_default_backend();
+        mixer.set_volume(settings.effects_volume(), settings.music_volume());
+
+        App {
+            gl: GlGraphics::new(opengl),
+            glyphs: glyphs,
+            game: game,
+            screens: vec![ScreenState { screen: Screen::Title, selected: 0 }],
+            settings: settings,
+            theme: Theme::default(),
+            sheet: None,
+            rebinding: false,
+            quit: false,
+            save_slot: 1,
+            practice: false,
+            rewinding: false,
+            rewind: RewindBuffer::new(TICKS_PER_SECOND*REWIND_SECONDS),
+            time_scale: 1.0,
+            frame_time: 0.0,
+            debug: false,
+            view: View::default(),
+            camera: Camera::new(CAMERA_DEAD_ZONE, CAMERA_SMOOTHING),
+            minimap: true,
+            mixer: mixer,
+            effects: Effects::new(),
+            scores: scores,
+            new_high_score: false,
+            player_name: String::new(),
+            scores_level: 0,
+            scores_difficulty: Difficulty::Normal,
+            lifetime: lifetime,
+            notice: scores_error.or(lifetime_error)
+                                .map(|message| (message, NOTICE_FRAMES))
+        }
+    }
+
+    pub fn render(&mut self, args: &RenderArgs) {
+        self.view = View::fit([args.width as f64, args.height as f64],
+                                [args.draw_width, args.draw_height],
+                                self.settings.integer_scaling);
+
+        //slow motion skips some frames
+        self.frame_time += self.time_scale;
+        while self.frame_time >= 1.0 {
+            self.frame_time -= 1.0;
+            self.update();
+        }
+
+        if self.in_game() {
+            let hero = self.game.hero.object.clone();
+            self.camera.follow(hero.x + hero.width/2.0,
+                                hero.y + hero.height/2.0, self.game.map_size());
+            self.render_game(args);
+        }
+        if self.screen() != Screen::Playing {
+            self.render_menu(args);
+        }
+        self.render_notice(args);
+
+        //music of level is played until the game is over
+        let music = if self.in_game() && !self.game.game_over {
+            Some(self.game.level)
+        } else {
+            None
+        };
+        self.mixer.set_music(music);
+        self.mixer.end_frame();
+    }
+
+    //load theme and its sprite sheet, current theme is kept on errors
+    fn set_theme(&mut self, dir: Option<PathBuf>, color_blind: bool) {
+        let result = Theme::load(dir.as_ref().map(|dir| dir.as_path()),
+                                                                color_blind)
+            .and_then(|theme| {
+                let sheet = match &theme.sheet {
+                    Some(path) => Some(Texture::from_path(path,
+                                                    &TextureSettings::new())
+                        .map_err(|error| format!("Can't load sprite sheet \
+                                        {}: {}", path.display(), error))?),
+                    None => None
+                };
+                Ok((theme, sheet))
+            });
+
+        match result {
+            Ok((theme, sheet)) => {
+                self.theme = theme;
+                self.sheet = sheet;
+                self.settings.theme = dir;
+                self.settings.color_blind = color_blind;
+            },
+            Err(message) => self.notify(message)
+        }
+    }
+
+    //show message for the player over the top of the field for a while
+    fn notify(&mut self, message: String) {
+        self.notice = Some((message, NOTICE_FRAMES));
+    }
+
+    fn render_notice(&mut self, args: &RenderArgs) {
+        const NOTICE_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.8];
+        const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+        use graphics::*;
+
+        let message = match self.notice.take() {
+            Some((message, frames)) => {
+                if frames > 1 {
+                    self.notice = Some((message.clone(), frames - 1));
+                }
+                message
+            },
+            None => return
+        };
+        let glyphs = &mut self.glyphs;
+        let view = self.view;
+        let zoom = view.pixel_scale();
+
+        self.gl.draw(args.viewport(), |c, gl| {
+            let c = c.trans(view.x, view.y).zoom(view.scale);
+            let panel = [4.0, 4.0, WIDTH_CELL_SIZE*17.0 - 8.0,
+                                                        HUD_LINE_HEIGHT + 4.0];
+            rectangle(NOTICE_COLOR, panel, c.transform, gl);
+            draw_text(&message, HUD_FONT_SIZE, WHITE, WIDTH_CELL_SIZE*8.5,
+                        HUD_LINE_HEIGHT + 1.0, true, zoom, glyphs, &c, gl);
+        });
+    }
+
+    //player wants to close the program
+    pub fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    //window should be shown on the whole screen
+    pub fn fullscreen(&self) -> bool {
+        self.settings.fullscreen
+    }
+
+    fn render_game(&mut self, args: &RenderArgs) {
+        //const SLIDE_COLOR: [f32; 4] = [1.0, 0.9, 0.3, 1.0];
+        //const HOLE_COLOR: [f32; 4] = [0.1, 0.1, 0.1, 1.0];
+        const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+        const GREEN: [f32; 4] = [0.0, 1.0, 0.0, 1.0];
+        const RED:   [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+        const YELLOW: [f32; 4] = [1.0, 1.0, 0.0, 1.0];
+
+        const HEALTH_BACK_COLOR: [f32; 4] = [0.3, 0.3, 0.3, 1.0];
+        const LETTERBOX_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+
+
+        use graphics::*;
+//        println!("FRAME {}", self.counter);
+//        self.counter += 1;
+
+        let lives = self.game.hero.lives;
+        let point_num = self.game.point_num;
+        let crash_num = self.game.crash_num;
+        let score = self.game.score();
+        let multiplier = self.game.scoring.multiplier();
+        let seconds = self.game.ticks/(TICKS_PER_SECOND as u64);
+
+        let level_name = self.game.level_name();
+        //field doesn't shake behind menus
+        let playing = self.screen() == Screen::Playing;
+
+        let blocks = &self.game.blocks;
+        let enimies = &self.game.enimies;
+        let free_bullets = &self.game.free_bullets;
+        let hero = &self.game.hero;
+        //hero is away until respawn and blinks while invulnerable
+        let hero_visible = self.game.hero_on_field() && !hero.blinking();
+        let (debug_marks, debug_labels) = if self.debug {
+            (debug::overlay(&self.game), debug::labels(&self.game))
+        } else {
+            (vec![], vec![])
+        };
+        let minimap_marks = if self.minimap {
+            let rect = [WIDTH_CELL_SIZE*17.0 + WIDTH_HUD_SEGMENT_SIZE/2.0,
+                        HUD_LINE_HEIGHT*7.0,
+                        LOGICAL_WIDTH - WIDTH_CELL_SIZE*17.0 -
+                                                    WIDTH_HUD_SEGMENT_SIZE,
+                        HEIGHT_CELL_SIZE*4.0];
+            camera::minimap(&self.game, &self.camera, rect)
+        } else {
+            vec![]
+        };
+        let glyphs = &mut self.glyphs;
+        let theme = &self.theme;
+        let sheet = self.sheet.as_ref();
+        let view = self.view;
+        let camera_x = self.camera.x;
+        let camera_y = self.camera.y;
+        let effect_marks = self.effects.marks();
+        let (shake_x, shake_y) = if playing {
+            self.effects.shake()
+        } else {
+            (0.0, 0.0)
+        };
+
+        //drawing
+        self.gl.draw(args.viewport(), |c, gl| {
+            //clear the window, free space around logical screen stays black
+            clear(LETTERBOX_COLOR, gl);
+            let screen = c.trans(view.x, view.y).zoom(view.scale);
+            rectangle(theme.background, [0.0, 0.0, LOGICAL_WIDTH,
+                                        LOGICAL_HEIGHT], screen.transform, gl);
+
+            //map is seen by camera only inside field view
+            let mut c = screen.trans(shake_x - camera_x, shake_y - camera_y);
+            c.draw_state = c.draw_state.scissor(view.pixel_rect([0.0, 0.0,
+                                        FIELD_VIEW_WIDTH, FIELD_VIEW_HEIGHT]));
+            let transform = c.transform;
+
+            for block in blocks {
+                let obj = &block.object;
+                let square = rectangle::square(obj.x, obj.y,
+                                                obj.width);
+                draw_look(theme.block(block.block_type), square, sheet,
+                                                    theme.tile_size, &c, gl);
+            }
+
+            for enimy in enimies {
+                let obj = enimy.body();
+                let square = rectangle::square(obj.x, obj.y,
+                                                obj.width);
+                draw_look(theme.robot(enimy), square, sheet,
+                                                    theme.tile_size, &c, gl);
+            }
+
+            let obj = hero.body();
+            let square = rectangle::square(obj.x, obj.y,
+                                            obj.width);
+            if hero_visible {
+                draw_look(theme.robot(hero), square, sheet,
+                                                    theme.tile_size, &c, gl);
+            }
+
+            //bullets are drawn by look of their owner
+            let enimy_bullets = enimies.iter()
+                                    .flat_map(|enimy| enimy.bullets.iter())
+                                    .map(|bullet| (bullet, BulletOwner::Enimy));
+            let bullets = hero.bullets.iter()
+                            .map(|bullet| (bullet, BulletOwner::Hero))
+                            .chain(free_bullets.iter()
+                                    .map(|bullet| (bullet, BulletOwner::Free)))
+                            .chain(enimy_bullets);
+            for (bullet, owner) in bullets {
+                let obj = &bullet.object;
+                let square = rectangle::square(obj.x, obj.y,
+                                                obj.width);
+                draw_look(theme.bullet(owner), square, sheet,
+                                                    theme.tile_size, &c, gl);
+            }
+
+            for mark in effect_marks.iter().chain(debug_marks.iter()) {
+                rectangle(mark.color, mark.rect, transform, gl);
+            }
+            for label in &debug_labels {
+                draw_text(&label.text, debug::LABEL_SIZE, label.color,
+                            label.x, label.y, false, view.pixel_scale(),
+                            glyphs, &c, gl);
+            }
+
+            //indicators are drawn over screen, not over map
+            let c = screen;
+            let transform = c.transform;
+            let left_hud_border = WIDTH_CELL_SIZE*17.0 +
+                                    WIDTH_HUD_SEGMENT_SIZE/2.0;
+
+            //print level, time, score, lives, killed and crashed enemies
+            let hud = [
+                (String::from(level_name), WHITE),
+                (format!("Time {}:{:02}", seconds/60, seconds%60), WHITE),
+                (if multiplier > 1 {
+                    format!("Score {} x{}", score, multiplier)
+                } else {
+                    format!("Score {}", score)
+                }, WHITE),
+                (format!("Lives {}", lives.max(0)), GREEN),
+                (format!("Kills {}", point_num), RED),
+                (format!("Crashes {}", crash_num), YELLOW)
+            ];
+            for (i, (line, color)) in hud.iter().enumerate() {
+                let baseline = HUD_LINE_HEIGHT*((i + 1) as f64);
+                draw_text(line, HUD_FONT_SIZE, *color, left_hud_border,
+                                baseline, false, view.pixel_scale(), glyphs,
+                                &c, gl);
+            }
+
+            for mark in &minimap_marks {
+                rectangle(mark.color, mark.rect, transform, gl);
+            }
+
+            //print lives of boss as health bar under the field
+            let boss = enimies.iter()
+                            .find(|enimy| enimy.robot_type == RobotType::Boss);
+            if let Some(boss) = boss {
+                let max_lives = boss.robot_type.stats().lives as f64;
+                let bar_width = WIDTH_CELL_SIZE*15.0;
+                let bar_top = HEIGHT_CELL_SIZE*14.0 + HEIGHT_HUD_SEGMENT_SIZE;
+                let back = [WIDTH_CELL_SIZE, bar_top,
+                                bar_width, HEIGHT_HUD_SEGMENT_SIZE*2.0];
+                let health = [WIDTH_CELL_SIZE, bar_top,
+                                bar_width*(boss.lives as f64)/max_lives,
+                                HEIGHT_HUD_SEGMENT_SIZE*2.0];
+                rectangle(HEALTH_BACK_COLOR, back, transform, gl);
+                rectangle(boss.color(), health, transform, gl);
+            }
+        });
+    }
+
+    //one tick forward or back in time if it's rewinding
+    fn update(&mut self) {
+        if !self.in_game() {
+            return;
+        }
+
+        //pause menu stays while the game is rewound
+        if self.practice && self.rewinding {
+            if let Some(snapshot) = self.rewind.pop() {
+                let paused = self.game.paused;
+                self.game.restore(&snapshot);
+                self.effects.clear();
+                self.game.paused = paused;
+                self.sync_screens();
+            }
+            return;
+        }
+
+        if self.screen() == Screen::Playing {
+            self.advance();
+            self.sync_screens();
+        } else if self.game.game_over {
+            //effects of the last tick fade out behind results
+            self.effects.tick();
+        }
+    }
+
+    //one tick forward, state before it is remembered in practice mode
+    fn advance(&mut self) {
+        if self.practice && !self.game.game_over {
+            self.rewind.push(self.game.snapshot());
+        }
+        self.game.step();
+        self.effects.tick();
+        for event in self.game.events.iter() {
+            if let Some(sound) = audio::sound(event) {
+                self.mixer.play(sound);
+            }
+            self.effects.event(event, &self.game.hero);
+            match event {
+                GameEvent::LevelWon | GameEvent::LevelFailed => {
+                    let game = &self.game;
+                    self.new_high_score = !self.practice &&
+                            self.scores.qualifies(game.level, game.difficulty,
+                                                                game.score());
+                    if !self.practice {
+                        if let Err(message) = self.lifetime.add(&game.stats) {
+                            self.notice = Some((message, NOTICE_FRAMES));
+                        }
+                    }
+                },
+                _ => {}
+            }
+        }
+    }
+
+    //change speed of the game: normal, half and quarter
+    fn input_time(&mut self, button: &Button) -> bool {
+        match button {
+            Button::Keyboard(Key::Z) => {
+                self.time_scale = if self.time_scale > 0.75 {
+                    0.5
+                } else if self.time_scale > 0.375 {
+                    0.25
+                } else {
+                    1.0
+                };
+                self.notify(format!("Speed of game is {}x", self.time_scale));
+                true
+            },
+            _ => false
+        }
+    }
+
+    //show debug overlay and print state of robots,
+    //return true if button is processed
+    fn input_debug(&mut self, button: &Button) -> bool {
+        match button {
+            Button::Keyboard(Key::F3) => {
+                self.debug = !self.debug;
+                true
+            },
+            Button::Keyboard(Key::F4) => {
+                print!("{}", debug::report(&self.game));
+                true
+            },
+            _ => false
+        }
+    }
+
+    //switch practice mode and hold rewind key,
+    //return true if button is processed
+    fn input_practice(&mut self, button: &Button) -> bool {
+        match button {
+            Button::Keyboard(Key::F2) => {
+                self.practice = !self.practice;
+                self.rewind.clear();
+                self.effects.clear();
+                self.notify(format!("Practice mode is {}",
+                                if self.practice { "on" } else { "off" }));
+                true
+            },
+            Button::Keyboard(Key::Backspace) => {
+                self.rewinding = true;
+                true
+            },
+            _ => false
+        }
+    }
+
+    //processing of released buttons
+    pub fn release(&mut self, button: &Button) {
+        if let Button::Keyboard(Key::Backspace) = button {
+            self.rewinding = false;
+        }
+    }
+
+    //file of current save slot
+    fn save_path(&self) -> PathBuf {
+        Path::new(SAVE_DIR).join(format!("slot{}.txt", self.save_slot))
+    }
+
+    //choose slot, save and load game in any state of it,
+    //return true if button is processed
+    fn input_saves(&mut self, button: &Button) -> bool {
+        let key = match button {
+            Button::Keyboard(key) => *key,
+            _ => return false
+        };
+
+        match key {
+            Key::D1 | Key::D2 | Key::D3 => {
+                self.save_slot = match key {
+                    Key::D1 => 1,
+                    Key::D2 => 2,
+                    _ => 3
+                };
+                self.notify(format!("Save slot {} is chosen",
+                                                            self.save_slot));
+            },
+            Key::F5 => {
+                let result = fs::create_dir_all(SAVE_DIR)
+                    .map_err(|error| error.to_string())
+                    .and_then(|_| self.game.save(&self.save_path()));
+                let message = match result {
+                    Ok(()) => format!("Game is saved to slot {}",
+                                                            self.save_slot),
+                    Err(message) => message
+                };
+                self.notify(message);
+            },
+            Key::F9 => {
+                self.rewind.clear();
+                self.effects.clear();
+                self.camera.reset();
+                let message = match self.game.load(&self.save_path()) {
+                    Ok(()) => format!("Game is loaded from slot {}",
+                                                            self.save_slot),
+                    Err(message) => message
+                };
+                self.notify(message);
+                self.sync_screens();
+            },
+            _ => return false
+        }
+        true
+    }
+
+    pub fn input(&mut self, button: &Button) {
+
+        if *button == Button::Keyboard(Key::F11) && !self.rebinding {
+            self.settings.fullscreen = !self.settings.fullscreen;
+            return;
+        }
+
+        //keys are typed into name of player
+        let typing = self.screen() == Screen::NameEntry;
+        if self.in_game() && !self.rebinding && !typing &&
+                    (self.input_saves(button) || self.input_practice(button) ||
+                    self.input_time(button) || self.input_debug(button)) {
+            return;
+        }
+
+        match self.screen() {
+            Screen::Playing => self.input_playing(button),
+            _ => self.input_menu(button)
+        }
+    }
+
+    //typed text, it's used only for name of player in high scores
+    pub fn text(&mut self, text: &str) {
+        if self.screen() == Screen::NameEntry {
+            let name = self.player_name.clone() + text;
+            self.player_name = scores::clean_name(&name);
+        }
+    }
+
+    //control of the hero by bound keys
+    fn input_playing(&mut self, button: &Button) {
+        let key = match button {
+            Button::Keyboard(key) => *key,
+            _ => {
+                println!("Non keyboard button");
+                return;
+            }
+        };
+
+        match key {
+            Key::P | Key::Escape => {
+                self.game.paused = true;
+                self.mixer.play(Sound::Pause);
+                self.sync_screens();
+                return;
+            },
+            Key::M => {
+                self.minimap = !self.minimap;
+                return;
+            },
+            _ => {}
+        }
+
+        let hero = &self.game.hero;
+        let mut new_direct = Direct::NONE;
+        let mut new_fire_direct = hero.prepare_fire;
+        match self.settings.action(key) {
+            Some(HeroAction::Stop) => new_direct = hero.next_direct,
+            Some(HeroAction::MoveUp) => new_direct = Direct::UP,
+            Some(HeroAction::MoveDown) => new_direct = Direct::DOWN,
+            Some(HeroAction::MoveLeft) => new_direct = Direct::LEFT,
+            Some(HeroAction::MoveRight) => new_direct = Direct::RIGHT,
+            Some(HeroAction::FireUp) => new_fire_direct = Direct::UP,
+            Some(HeroAction::FireDown) => new_fire_direct = Direct::DOWN,
+            Some(HeroAction::FireLeft) => new_fire_direct = Direct::LEFT,
+            Some(HeroAction::FireRight) => new_fire_direct = Direct::RIGHT,
+            None => println!("Another keyboard button")
+        }
+
+        //access move changing
+        let hero = &mut self.game.hero;
+        if hero.next_direct == new_direct {
+            hero.next_direct = Direct::NONE;
+        } else if new_direct != Direct::NONE {
+            hero.next_direct = new_direct;
+        }
+
+        //access creating bullet
+        hero.prepare_fire = new_fire_direct;
+    }
+}
+
+//draw look of theme into rectangle
+fn draw_look(look: Look, rect: [f64; 4], sheet: Option<&Texture>,
+                tile_size: f64, c: &Context, gl: &mut GlGraphics) {
+    use graphics::{rectangle, Image};
+
+    match (look, sheet) {
+        (Look::Color(color), _) => rectangle(color, rect, c.transform, gl),
+        (Look::Tile(column, row), Some(sheet)) => {
+            let source = [(column as f64)*tile_size, (row as f64)*tile_size,
+                                                        tile_size, tile_size];
+            Image::new().src_rect(source).rect(rect)
+                                .draw(sheet, &c.draw_state, c.transform, gl);
+        },
+        //theme without sheet can't have tiles
+        (Look::Tile(_, _), None) => {}
+    }
+}
+
+//draw text with baseline at y, starting at x or centered around it,
+//glyphs are rendered for zoom (pixels in logical pixel) to be sharp
+fn draw_text(text: &str, size: u32, color: [f32; 4], x: f64, y: f64,
+                centered: bool, zoom: f64, glyphs: &mut GlyphCache<'static>,
+                c: &Context, gl: &mut GlGraphics) {
+    use graphics::{Text, Transformed};
+    use graphics::character::CharacterCache;
+
+    let font_size = ((size as f64)*zoom).round().max(1.0) as u32;
+    let left = if centered {
+        x - glyphs.width(font_size, text).unwrap_or(0.0)/zoom/2.0
+    } else {
+        x
+    };
+    //missing glyphs are skipped
+    let transform = c.transform.trans(left, y).zoom(1.0/zoom);
+    let _ = Text::new_color(color, font_size)
+                .draw(text, glyphs, &c.draw_state, transform, gl);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::GameRng;
+
+    //robots stand on their places and don't shoot
+    struct Still;
+
+    impl Controller for Still {
+        fn decide(&mut self, _view: &GameView, robots: &[Robot],
+                                    _rng: &mut GameRng) -> Vec<Command> {
+            robots.iter().map(|robot| Command {
+                direct: Direct::NONE,
+                fire: Direct::NONE,
+                state: robot.action_state
+            }).collect()
+        }
+    }
+
+    fn robot_at(robot_type: RobotType, column: i64, row: i64) -> Robot {
+        Robot::new((column as f64)*WIDTH_CELL_SIZE,
+                    (row as f64)*HEIGHT_CELL_SIZE, robot_type,
+                    EnimyState::WaitLeft)
+    }
+
+    //first level with standing hero at (9, 11) and only these enemies
+    fn arena(enimies: Vec<Robot>) -> Game {
+        let mut game = Game::new();
+        game.set_level(0);
+        game.create_level();
+        game.reseed(1);
+        game.boss_pending = false;
+        game.enimies = enimies;
+        game.set_enimies_controller(Box::new(Still));
+        game.set_hero_controller(Some(Box::new(Still)));
+        game
+    }
+
+    //bullet flying left to robot from the cell on its right
+    fn bullet_to(robot: &Robot) -> Bullet {
+        let x = robot.object.x + robot.object.width + 8.0;
+        let y = robot.object.y + robot.object.height/2.0 - 4.0;
+        Bullet::new(x, y, 8.0, 8.0, Direct::LEFT)
+    }
+
+    //events of the next ticks
+    fn run(game: &mut Game, ticks: usize) -> Vec<GameEvent> {
+        let mut events = vec![];
+        for _ in 0..ticks {
+            game.step();
+            events.extend_from_slice(game.events());
+        }
+        events
+    }
+
+    fn hero_hits(events: &[GameEvent]) -> Vec<DeathCause> {
+        events.iter().filter_map(|event| match *event {
+            GameEvent::HeroHit { cause, .. } => Some(cause),
+            _ => None
+        }).collect()
+    }
+
+    #[test]
+    fn hit_takes_one_life_and_makes_enemy_invulnerable() {
+        let mut game = arena(vec![robot_at(RobotType::Tank, 5, 11)]);
+        let bullet = bullet_to(&game.enimies[0]);
+        game.hero.bullets.push(bullet);
+        run(&mut game, 4);
+
+        let tank = &game.enimies[0];
+        assert_eq!(tank.lives, 2);
+        assert!(!tank.vulnerable());
+        assert!(tank.flashing());
+        assert!(game.hero.bullets.is_empty());
+        assert_eq!(game.scoring.hits, scoring::HIT_POINTS);
+
+        //bullet is destroyed, but takes no life while enemy is invulnerable
+        let bullet = bullet_to(&game.enimies[0]);
+        game.hero.bullets.push(bullet);
+        run(&mut game, 4);
+        assert_eq!(game.enimies[0].lives, 2);
+        assert!(game.hero.bullets.is_empty());
+
+        run(&mut game, INVULNERABLE_TICKS as usize);
+        let bullet = bullet_to(&game.enimies[0]);
+        game.hero.bullets.push(bullet);
+        run(&mut game, 4);
+        assert_eq!(game.enimies[0].lives, 1);
+    }
+
+    #[test]
+    fn last_life_destroys_enemy() {
+        let mut game = arena(vec![robot_at(RobotType::Soldier, 5, 11),
+                                    robot_at(RobotType::Soldier, 3, 3)]);
+        let bullet = bullet_to(&game.enimies[0]);
+        game.hero.bullets.push(bullet);
+        let events = run(&mut game, 4);
+
+        assert_eq!(game.enimies.len(), 1);
+        assert_eq!(game.point_num, 1);
+        assert!(events.iter().any(|event| match event {
+            GameEvent::EnemyKilled { by: Damager::HeroBullet, .. } => true,
+            _ => false
+        }));
+    }
+
+    #[test]
+    fn invulnerable_hero_is_not_hit_by_bullets() {
+        let mut game = arena(vec![robot_at(RobotType::Soldier, 3, 3)]);
+        game.hero.invulnerable = 20;
+        let bullet = bullet_to(&game.hero);
+        game.enimies[0].bullets.push(bullet);
+        let events = run(&mut game, 8);
+        assert_eq!(game.hero.lives, 3);
+        assert!(hero_hits(&events).is_empty());
+
+        game.hero.invulnerable = 0;
+        let bullet = bullet_to(&game.hero);
+        game.enimies[0].bullets.push(bullet);
+        let events = run(&mut game, 8);
+        assert_eq!(game.hero.lives, 2);
+        assert_eq!(hero_hits(&events), vec![DeathCause::EnemyBullet]);
+    }
+
+    #[test]
+    fn contact_damages_hero_and_enemy() {
+        let mut game = arena(vec![robot_at(RobotType::Soldier, 3, 3)]);
+        game.enimies.push(Robot::new(game.hero.object.x + 16.0,
+                                    game.hero.object.y, RobotType::Soldier,
+                                    EnimyState::Attack));
+        let events = run(&mut game, 1);
+
+        assert_eq!(game.hero.lives, 2);
+        assert_eq!(hero_hits(&events), vec![DeathCause::Contact]);
+        assert_eq!(game.enimies.len(), 1);
+        assert_eq!(game.crash_num, 1);
+    }
+
+    #[test]
+    fn invulnerable_enemy_still_damages_hero_by_contact() {
+        let mut game = arena(vec![robot_at(RobotType::Soldier, 3, 3)]);
+        let mut enimy = Robot::new(game.hero.object.x + 16.0,
+                                    game.hero.object.y, RobotType::Soldier,
+                                    EnimyState::Attack);
+        enimy.invulnerable = 20;
+        game.enimies.push(enimy);
+        let events = run(&mut game, 1);
+
+        assert_eq!(game.hero.lives, 2);
+        assert_eq!(hero_hits(&events), vec![DeathCause::Contact]);
+        //enemy isn't damaged
+        assert_eq!(game.enimies.len(), 2);
+        assert_eq!(game.enimies[1].lives, 1);
+    }
+
+    fn respawned(events: &[GameEvent]) -> bool {
+        events.iter().any(|event| match event {
+            GameEvent::HeroRespawned { .. } => true,
+            _ => false
+        })
+    }
+
+    #[test]
+    fn hero_respawns_after_delay() {
+        let mut game = arena(vec![robot_at(RobotType::Soldier, 3, 3)]);
+        game.hero_hit(DeathCause::EnemyBullet);
+        assert!(!game.hero_on_field());
+
+        let events = run(&mut game, RESPAWN_DELAY_TICKS as usize - 1);
+        assert!(!respawned(&events));
+        assert!(!game.hero_on_field());
+
+        let events = run(&mut game, 1);
+        assert!(respawned(&events));
+        assert!(game.hero_on_field());
+        assert_eq!(game.hero.lives, 2);
+    }
+
+    #[test]
+    fn hero_respawns_away_from_enemies() {
+        //enemy stands next to start of hero
+        let mut game = arena(vec![robot_at(RobotType::Tank, 9, 10)]);
+        game.hero_hit(DeathCause::Contact);
+        run(&mut game, RESPAWN_DELAY_TICKS as usize);
+        assert!(game.hero_on_field());
+
+        let (hero_x, hero_y) = center(&game.hero.object);
+        let (enimy_x, enimy_y) = center(&game.enimies[0].object);
+        let cells = ((hero_x - enimy_x)/WIDTH_CELL_SIZE).abs()
+                        .max(((hero_y - enimy_y)/HEIGHT_CELL_SIZE).abs());
+        assert!(cells >= SAFE_ENIMY_CELLS);
+        assert!(game.blocks.iter().any(|block| {
+            block.block_type == BlockType::NODE
+                && block.object.x == game.hero.object.x
+                && block.object.y == game.hero.object.y
+        }));
+    }
+
+    #[test]
+    fn respawned_hero_is_not_damaged_while_blinking() {
+        let mut game = arena(vec![robot_at(RobotType::Soldier, 3, 3)]);
+        game.hero_hit(DeathCause::EnemyBullet);
+        run(&mut game, RESPAWN_DELAY_TICKS as usize);
+        assert!(game.hero.invulnerable > 0);
+
+        let bullet = bullet_to(&game.hero);
+        game.enimies[0].bullets.push(bullet);
+        game.enimies.push(Robot::new(game.hero.object.x + 16.0,
+                                    game.hero.object.y, RobotType::Soldier,
+                                    EnimyState::Attack));
+        let events = run(&mut game, 8);
+        assert!(hero_hits(&events).is_empty());
+        assert_eq!(game.hero.lives, 2);
+
+        //hero can be damaged again when blinking is over
+        game.enimies.truncate(1);
+        run(&mut game, RESPAWN_INVULNERABLE_TICKS as usize);
+        assert!(game.hero.vulnerable());
+        let bullet = bullet_to(&game.hero);
+        game.enimies[0].bullets.push(bullet);
+        let events = run(&mut game, 8);
+        assert_eq!(hero_hits(&events), vec![DeathCause::EnemyBullet]);
+        assert_eq!(game.hero.lives, 1);
+    }
+}
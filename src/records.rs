@@ -0,0 +1,71 @@
+//records of text files
+//
+//Snapshots, themes, high scores and statistics are written as text with one
+//record per line: name of record and its values separated by spaces.
+//Enum values are written by their names, tables of variants are used
+//to read them back.
+
+use std::fmt::Debug;
+
+use crate::{BlockType, DeathCause, Direct, EnimyState, RobotType};
+
+//all variants of enums which are written in records
+pub(crate) const DIRECTS: [Direct; 5] = [Direct::NONE, Direct::UP,
+                                Direct::DOWN, Direct::LEFT, Direct::RIGHT];
+pub(crate) const ENIMY_STATES: [EnimyState; 14] = [
+    EnimyState::Manual, EnimyState::HideLeft, EnimyState::HideUp,
+    EnimyState::ToWaitLeft, EnimyState::ToWaitUp,
+    EnimyState::WaitLeft, EnimyState::WaitUp,
+    EnimyState::ToHideLeft, EnimyState::ToHideUp, EnimyState::Attack,
+    EnimyState::BossPatrol, EnimyState::BossSpread,
+    EnimyState::BossCharge, EnimyState::BossReturn];
+pub(crate) const ROBOT_TYPES: [RobotType; 6] = [RobotType::Hero,
+                                        RobotType::Soldier, RobotType::Tank,
+                                        RobotType::Scout, RobotType::Turret,
+                                        RobotType::Boss];
+pub(crate) const BLOCK_TYPES: [BlockType; 4] = [BlockType::NODE,
+                                        BlockType::HOLE, BlockType::WALL,
+                                        BlockType::SLIDE];
+pub(crate) const DEATH_CAUSES: [DeathCause; 4] = [DeathCause::OwnBullet,
+                                        DeathCause::EnemyBullet,
+                                        DeathCause::FreeBullet,
+                                        DeathCause::Contact];
+
+//find enum variant by its name
+pub(crate) fn variant<T: Debug + Copy>(word: &str, variants: &[T])
+                                                    -> Result<T, String> {
+    variants.iter()
+        .find(|variant| format!("{:?}", variant) == word)
+        .cloned()
+        .ok_or_else(|| format!("unknown value '{}'", word))
+}
+
+//reader of words of one record
+pub(crate) struct Words<'a>(pub(crate) std::str::SplitWhitespace<'a>);
+
+impl<'a> Words<'a> {
+    pub(crate) fn next(&mut self) -> Result<&'a str, String> {
+        self.0.next().ok_or_else(|| String::from("record is too short"))
+    }
+
+    pub(crate) fn parse<T: std::str::FromStr>(&mut self)
+                                                    -> Result<T, String> {
+        let word = self.next()?;
+        word.parse().map_err(|_| format!("wrong value '{}'", word))
+    }
+
+    //value at the end of record which can be missing
+    pub(crate) fn parse_or<T: std::str::FromStr>(&mut self, default: T)
+                                                    -> Result<T, String> {
+        match self.0.next() {
+            Some(word) => word.parse()
+                                .map_err(|_| format!("wrong value '{}'", word)),
+            None => Ok(default)
+        }
+    }
+
+    pub(crate) fn variant<T: Debug + Copy>(&mut self, variants: &[T])
+                                                    -> Result<T, String> {
+        variant(self.next()?, variants)
+    }
+}
@@ -0,0 +1,51 @@
+//rewinding of the game in practice mode
+//
+//Ring buffer keeps snapshots of the last ticks, while the player holds
+//rewind key the game goes back by one tick per frame and continues
+//from that moment when the key is released.
+
+use std::collections::VecDeque;
+
+use crate::snapshot::Snapshot;
+
+pub struct RewindBuffer {
+    snapshots: VecDeque<Snapshot>,
+    capacity: usize //count of kept ticks
+}
+
+impl RewindBuffer {
+    pub fn new(capacity: usize) -> RewindBuffer {
+        RewindBuffer {
+            snapshots: VecDeque::with_capacity(capacity),
+            capacity: capacity
+        }
+    }
+
+    //remember state before tick, the oldest one is forgotten if it's full
+    pub fn push(&mut self, snapshot: Snapshot) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.snapshots.len() >= self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+    }
+
+    //take state of previous tick
+    pub fn pop(&mut self) -> Option<Snapshot> {
+        self.snapshots.pop_back()
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+}
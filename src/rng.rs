@@ -0,0 +1,63 @@
+//random number generator of the game
+//
+//Small xorshift generator, its whole state is one number, so the game
+//can be repeated from the same seed and its state can be saved.
+
+use rand::{Error, RngCore};
+
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct GameRng {
+    state: u64
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> GameRng {
+        //mix seed, so close seeds give different sequences
+        //and zero state which xorshift can't leave is impossible
+        let mut state = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        state = (state ^ (state >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        state = (state ^ (state >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        state ^= state >> 31;
+        if state == 0 {
+            state = 0x9E37_79B9_7F4A_7C15;
+        }
+        GameRng { state: state }
+    }
+
+    //current state of generator, it continues the same sequence
+    //after restoring by from_state
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    pub fn from_state(state: u64) -> GameRng {
+        GameRng { state: if state == 0 { 1 } else { state } }
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            let len = chunk.len();
+            chunk.copy_from_slice(&bytes[..len]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
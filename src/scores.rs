@@ -0,0 +1,323 @@
+//table of high scores
+//
+//Best results of every level on every difficulty are kept in the file
+//in data directory of the user, so they stay after the game is closed.
+//Every result has name of the player, score, time, kills and crashes
+//of enemies and the date of the game. Player enters his name after the end
+//of the game if its result gets into the table of the level.
+//Results of practice mode are not recorded, the game can be rewound there.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{Difficulty, DIFFICULTIES};
+use crate::records::Words;
+
+//count of results in table of every level and difficulty
+pub const TABLE_SIZE: usize = 10;
+pub const MAX_NAME_LENGTH: usize = 12;
+
+const HEADER: &str = "crossfire high scores 1";
+const FILE_NAME: &str = "scores.txt";
+
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct ScoreEntry {
+    pub level: usize, //index of level in levels::LEVELS
+    pub difficulty: Difficulty,
+    pub name: String,
+    pub score: i64,
+    pub seconds: u64, //time of the game
+    pub kills: i64, //enemies destroyed by bullets of hero
+    pub crashes: i64, //enemies destroyed by collisions
+    pub date: String //day of the game as YYYY-MM-DD
+}
+
+pub struct HighScores {
+    path: PathBuf,
+    entries: Vec<ScoreEntry>
+}
+
+impl HighScores {
+    //table from the file in data directory of the user
+    pub fn load_default() -> (HighScores, Option<String>) {
+        HighScores::load(&data_dir().join(FILE_NAME))
+    }
+
+    //missing or broken file gives empty table, it's replaced by next result,
+    //error of broken file is returned for the player
+    pub fn load(path: &Path) -> (HighScores, Option<String>) {
+        let mut scores = HighScores { path: path.to_path_buf(),
+                                        entries: vec![] };
+        if !path.exists() {
+            return (scores, None);
+        }
+        let result = fs::read_to_string(path)
+            .map_err(|error| error.to_string())
+            .and_then(|text| from_text(&text));
+        match result {
+            Ok(entries) => {
+                scores.entries = entries;
+                (scores, None)
+            },
+            Err(message) => (scores, Some(format!("Can't load high scores \
+                                    from {}: {}", path.display(), message)))
+        }
+    }
+
+    //results of level on difficulty from the best one
+    pub fn table(&self, level: usize, difficulty: Difficulty)
+                                                    -> Vec<&ScoreEntry> {
+        let mut table: Vec<&ScoreEntry> = self.entries.iter()
+            .filter(|entry| entry.level == level &&
+                                            entry.difficulty == difficulty)
+            .collect();
+        //faster game is better with the same score
+        table.sort_by(|a, b| b.score.cmp(&a.score)
+                                    .then(a.seconds.cmp(&b.seconds)));
+        table.truncate(TABLE_SIZE);
+        table
+    }
+
+    //result gets into the table
+    pub fn qualifies(&self, level: usize, difficulty: Difficulty,
+                                                        score: i64) -> bool {
+        let table = self.table(level, difficulty);
+        let last_score = table.last().map_or(0, |last| last.score);
+        score > 0 && (table.len() < TABLE_SIZE || score > last_score)
+    }
+
+    //add result and write the table, results out of it are forgotten
+    pub fn add(&mut self, entry: ScoreEntry) -> Result<(), String> {
+        let (level, difficulty) = (entry.level, entry.difficulty);
+        self.entries.push(entry);
+        let kept: Vec<ScoreEntry> = self.table(level, difficulty).into_iter()
+                                                        .cloned().collect();
+        self.entries.retain(|entry| entry.level != level ||
+                                            entry.difficulty != difficulty);
+        self.entries.extend(kept);
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let error = |error: std::io::Error| {
+            format!("Can't save high scores to {}: {}", self.path.display(),
+                                                                        error)
+        };
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(error)?;
+        }
+        fs::write(&self.path, to_text(&self.entries)).map_err(error)
+    }
+}
+
+//text with one result per line, name is the last as it can have spaces
+fn to_text(entries: &[ScoreEntry]) -> String {
+    let mut text = String::from(HEADER);
+    text += "\n";
+    for entry in entries {
+        text += &format!("entry {} {:?} {} {} {} {} {} {}\n",
+                        entry.level, entry.difficulty, entry.score,
+                        entry.seconds, entry.kills, entry.crashes,
+                        entry.date, entry.name);
+    }
+    text
+}
+
+fn from_text(text: &str) -> Result<Vec<ScoreEntry>, String> {
+    let mut lines = text.lines().enumerate();
+    match lines.next() {
+        Some((_, line)) if line == HEADER => {},
+        _ => return Err(String::from("It is not a table of high scores"))
+    }
+
+    let mut entries = vec![];
+    for (line_num, line) in lines {
+        let error = |message: String| {
+            format!("line {}: {}", line_num + 1, message)
+        };
+        let mut words = Words(line.split_whitespace());
+        match words.next().map_err(&error)? {
+            "entry" => {
+                let level = words.parse().map_err(&error)?;
+                let difficulty = words.variant(&DIFFICULTIES)
+                                                        .map_err(&error)?;
+                let score = words.parse().map_err(&error)?;
+                let seconds = words.parse().map_err(&error)?;
+                let kills = words.parse().map_err(&error)?;
+                let crashes = words.parse().map_err(&error)?;
+                let date = words.next().map_err(&error)?.to_string();
+                let name: Vec<&str> = words.0.collect();
+                entries.push(ScoreEntry {
+                    level: level, difficulty: difficulty,
+                    name: name.join(" "), score: score, seconds: seconds,
+                    kills: kills, crashes: crashes, date: date
+                });
+            },
+            record => return Err(error(format!("unknown record '{}'",
+                                                                    record)))
+        }
+    }
+    Ok(entries)
+}
+
+//directory of the game in data directory of the user: XDG_DATA_HOME
+//or ~/.local/share on Linux, ~/Library/Application Support on macOS,
+//APPDATA on Windows, current directory if none of them is known
+pub fn data_dir() -> PathBuf {
+    let home = || env::var_os("HOME").map(PathBuf::from);
+    let base = if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| home().map(|home| home.join(".local").join("share")))
+    };
+    base.unwrap_or_else(|| PathBuf::from(".")).join("crossfire")
+}
+
+//current date in UTC as YYYY-MM-DD
+pub fn today() -> String {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH)
+                                    .map(|time| time.as_secs()).unwrap_or(0);
+    let (year, month, day) = civil_date((seconds/86400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+//year, month and day of count of days from 1970-01-01, days are not
+//negative (algorithm of Howard Hinnant)
+fn civil_date(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days/146097;
+    let day_of_era = days - era*146097;
+    let year_of_era = (day_of_era - day_of_era/1460 + day_of_era/36524
+                                            - day_of_era/146096)/365;
+    let day_of_year = day_of_era - (365*year_of_era + year_of_era/4
+                                                        - year_of_era/100);
+    let month_index = (5*day_of_year + 2)/153;
+    let day = day_of_year - (153*month_index + 2)/5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era*400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+//name typed by player is cut to the length and has only letters, digits,
+//hyphens, underscores and single spaces between words, so it's read back
+//from the table as it is
+pub fn clean_name(name: &str) -> String {
+    let mut clean = String::new();
+    for c in name.chars() {
+        let c = if c.is_whitespace() { ' ' } else { c };
+        let space_allowed = !clean.is_empty() && !clean.ends_with(' ');
+        if c.is_alphanumeric() || c == '-' || c == '_'
+                                                || (c == ' ' && space_allowed) {
+            clean.push(c);
+        }
+    }
+    clean.chars().take(MAX_NAME_LENGTH).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(level: usize, difficulty: Difficulty, name: &str, score: i64)
+                                                            -> ScoreEntry {
+        ScoreEntry {
+            level: level, difficulty: difficulty, name: name.to_string(),
+            score: score, seconds: 95, kills: 7, crashes: 2,
+            date: String::from("2024-02-29")
+        }
+    }
+
+    fn scores(entries: Vec<ScoreEntry>) -> HighScores {
+        HighScores { path: PathBuf::new(), entries: entries }
+    }
+
+    #[test]
+    fn text_restores_equal_entries() {
+        let entries = vec![entry(0, Difficulty::Normal, "Player", 1200),
+                            entry(3, Difficulty::Hard, "Big Bob", 80),
+                            entry(1, Difficulty::Easy, "a_b-c", 0)];
+        let text = to_text(&entries);
+        let restored = from_text(&text).unwrap();
+        assert_eq!(to_text(&restored), text);
+        assert_eq!(restored[1].name, "Big Bob");
+        assert_eq!(restored[1].difficulty, Difficulty::Hard);
+        assert_eq!(restored[1].seconds, 95);
+    }
+
+    #[test]
+    fn malformed_text_is_rejected() {
+        let good = to_text(&[entry(0, Difficulty::Normal, "Player", 100)]);
+        assert!(from_text(&good).is_ok());
+
+        let broken = [
+            "crossfire statistics 1\n",
+            "",
+            "crossfire high scores 1\nentry 0 Normal 100\n",
+            "crossfire high scores 1\nentry 0 Insane 100 95 7 2 2024-02-29 A\n",
+            "crossfire high scores 1\nentry x Normal 100 95 7 2 2024-02-29 A\n",
+            "crossfire high scores 1\nscore 0 Normal 100 95 7 2 2024-02-29 A\n",
+            "crossfire high scores 1\n\n"
+        ];
+        for text in broken.iter() {
+            assert!(from_text(text).is_err(), "accepted {:?}", text);
+        }
+    }
+
+    #[test]
+    fn qualifies_at_limit_of_table() {
+        let mut entries: Vec<ScoreEntry> = (1..TABLE_SIZE as i64)
+            .map(|place| entry(0, Difficulty::Normal, "Player", place*100))
+            .collect();
+        let table = scores(entries.clone());
+        assert!(table.qualifies(0, Difficulty::Normal, 1));
+        assert!(!table.qualifies(0, Difficulty::Normal, 0));
+
+        entries.push(entry(0, Difficulty::Normal, "Player", 50));
+        let table = scores(entries.clone());
+        assert_eq!(table.table(0, Difficulty::Normal).len(), TABLE_SIZE);
+        assert!(!table.qualifies(0, Difficulty::Normal, 49));
+        assert!(!table.qualifies(0, Difficulty::Normal, 50));
+        assert!(table.qualifies(0, Difficulty::Normal, 51));
+        //other tables aren't full
+        assert!(table.qualifies(1, Difficulty::Normal, 1));
+        assert!(table.qualifies(0, Difficulty::Hard, 1));
+
+        entries.push(entry(0, Difficulty::Normal, "Player", 20));
+        let table = scores(entries);
+        assert_eq!(table.table(0, Difficulty::Normal).len(), TABLE_SIZE);
+        assert!(!table.qualifies(0, Difficulty::Normal, 50));
+    }
+
+    #[test]
+    fn civil_date_of_known_days() {
+        assert_eq!(civil_date(0), (1970, 1, 1));
+        assert_eq!(civil_date(59), (1970, 3, 1));
+        assert_eq!(civil_date(10957), (2000, 1, 1));
+        assert_eq!(civil_date(11016), (2000, 2, 29));
+        assert_eq!(civil_date(19723), (2024, 1, 1));
+        assert_eq!(civil_date(24855), (2038, 1, 19));
+    }
+
+    #[test]
+    fn clean_name_keeps_single_spaces_between_words() {
+        assert_eq!(clean_name("Big Bob"), "Big Bob");
+        assert_eq!(clean_name("  Big \t  Bob"), "Big Bob");
+        assert_eq!(clean_name("Big Bob "), "Big Bob ");
+        assert_eq!(clean_name("a|b,c;d:e/f"), "abcdef");
+        assert_eq!(clean_name("x-y_z\n"), "x-y_z ");
+        assert_eq!(clean_name("Bob123456789012"), "Bob123456789");
+        assert_eq!(clean_name("Жора"), "Жора");
+    }
+}
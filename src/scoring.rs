@@ -0,0 +1,199 @@
+//score of the game
+//
+//Hero gets points for every hit of enemy by his bullet and points of type
+//of enemy for destroying it. Enemies crashed into each other near the hero
+//are counted as lured by him: they give their points and crash bonus.
+//Kills following each other quickly make combo, points of every next kill
+//in combo are multiplied by count of kills in it. When level is won, hero
+//gets bonus for time faster than par of level and for every life left.
+//Score is kept by its sources, so results can show where it came from.
+
+use crate::{RobotType, TICKS_PER_SECOND};
+
+pub const HIT_POINTS: i64 = 10;
+pub const CRASH_BONUS: i64 = 100;
+//ticks after kill while next kill continues combo
+pub const COMBO_TICKS: u32 = 2*TICKS_PER_SECOND as u32;
+pub const MAX_COMBO: i64 = 5;
+pub const TIME_BONUS_PER_SECOND: i64 = 20;
+pub const LIFE_BONUS: i64 = 500;
+
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy)]
+pub struct Scoring {
+    pub hits: i64, //points for damaging enemies
+    pub kills: i64, //points of types of enemies destroyed by bullets
+    pub crashes: i64, //points and bonuses of crashed enemies lured by hero
+    pub combo: i64, //extra points of combo multiplier
+    pub time: i64, //bonus for clearing level before par time
+    pub lives: i64, //bonus for lives left
+    pub chain: i64, //count of kills in current combo
+    pub chain_timer: u32 //ticks left to continue combo
+}
+
+impl Scoring {
+    pub fn new() -> Scoring {
+        Scoring {
+            hits: 0, kills: 0, crashes: 0, combo: 0, time: 0, lives: 0,
+            chain: 0, chain_timer: 0
+        }
+    }
+
+    pub fn total(&self) -> i64 {
+        self.hits + self.kills + self.crashes + self.combo +
+                                                        self.time + self.lives
+    }
+
+    //multiplier of points of the next kill if combo continues
+    pub fn multiplier(&self) -> i64 {
+        self.chain.max(1).min(MAX_COMBO)
+    }
+
+    pub fn hit(&mut self) {
+        self.hits += HIT_POINTS;
+    }
+
+    //enemy destroyed by bullet of hero
+    pub fn kill(&mut self, robot_type: RobotType) {
+        let points = robot_type.stats().points;
+        self.kills += points;
+        self.chain_kill(points);
+    }
+
+    //enemy destroyed by crash near hero
+    pub fn crash(&mut self, robot_type: RobotType) {
+        let points = robot_type.stats().points + CRASH_BONUS;
+        self.crashes += points;
+        self.chain_kill(points);
+    }
+
+    //combo ends when its time is over
+    pub fn tick(&mut self) {
+        if self.chain_timer > 0 {
+            self.chain_timer -= 1;
+            if self.chain_timer == 0 {
+                self.chain = 0;
+            }
+        }
+    }
+
+    //combo ends when hero loses life
+    pub fn break_combo(&mut self) {
+        self.chain = 0;
+        self.chain_timer = 0;
+    }
+
+    //bonuses of won level
+    pub fn finish(&mut self, seconds: u64, par_seconds: u64, lives: i64) {
+        let saved = par_seconds.saturating_sub(seconds) as i64;
+        self.time = saved*TIME_BONUS_PER_SECOND;
+        self.lives = lives.max(0)*LIFE_BONUS;
+    }
+
+    //sources of score with their points
+    pub fn breakdown(&self) -> [(&'static str, i64); 6] {
+        [("Hits", self.hits), ("Kills", self.kills),
+            ("Crashes", self.crashes), ("Combo", self.combo),
+            ("Time", self.time), ("Lives", self.lives)]
+    }
+
+    //kill continues combo or starts new one
+    fn chain_kill(&mut self, points: i64) {
+        self.chain = if self.chain_timer > 0 { self.chain + 1 } else { 1 };
+        self.chain_timer = COMBO_TICKS;
+        self.combo += points*(self.multiplier() - 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(robot_type: RobotType) -> i64 {
+        robot_type.stats().points
+    }
+
+    #[test]
+    fn combo_ends_after_its_time() {
+        let mut scoring = Scoring::new();
+        scoring.kill(RobotType::Soldier);
+        for _ in 0..COMBO_TICKS - 1 {
+            scoring.tick();
+        }
+        assert_eq!(scoring.multiplier(), 1);
+        scoring.kill(RobotType::Soldier);
+        assert_eq!(scoring.chain, 2);
+        assert_eq!(scoring.combo, points(RobotType::Soldier));
+
+        for _ in 0..COMBO_TICKS {
+            scoring.tick();
+        }
+        assert_eq!(scoring.chain, 0);
+        scoring.kill(RobotType::Soldier);
+        assert_eq!(scoring.chain, 1);
+        assert_eq!(scoring.combo, points(RobotType::Soldier));
+    }
+
+    #[test]
+    fn combo_multiplier_is_capped() {
+        let mut scoring = Scoring::new();
+        for _ in 0..MAX_COMBO + 3 {
+            scoring.kill(RobotType::Soldier);
+        }
+        assert_eq!(scoring.multiplier(), MAX_COMBO);
+        //points of kills are multiplied by 1, 2, 3, 4, 5, 5, 5, 5,
+        //combo has extra points over single points of every kill
+        let extra = (1 + 2 + 3 + 4*4)*points(RobotType::Soldier);
+        assert_eq!(scoring.combo, extra);
+    }
+
+    #[test]
+    fn crash_continues_combo() {
+        let mut scoring = Scoring::new();
+        scoring.kill(RobotType::Tank);
+        scoring.crash(RobotType::Scout);
+        let crash = points(RobotType::Scout) + CRASH_BONUS;
+        assert_eq!(scoring.crashes, crash);
+        assert_eq!(scoring.combo, crash);
+    }
+
+    #[test]
+    fn hero_hit_breaks_combo() {
+        let mut scoring = Scoring::new();
+        scoring.kill(RobotType::Soldier);
+        scoring.kill(RobotType::Soldier);
+        scoring.break_combo();
+        assert_eq!(scoring.chain, 0);
+        assert_eq!(scoring.chain_timer, 0);
+        scoring.kill(RobotType::Soldier);
+        assert_eq!(scoring.multiplier(), 1);
+        assert_eq!(scoring.combo, points(RobotType::Soldier));
+    }
+
+    #[test]
+    fn time_bonus_stops_at_par() {
+        let mut scoring = Scoring::new();
+        scoring.finish(60, 90, 2);
+        assert_eq!(scoring.time, 30*TIME_BONUS_PER_SECOND);
+        assert_eq!(scoring.lives, 2*LIFE_BONUS);
+
+        scoring.finish(90, 90, 0);
+        assert_eq!(scoring.time, 0);
+        scoring.finish(200, 90, -1);
+        assert_eq!(scoring.time, 0);
+        assert_eq!(scoring.lives, 0);
+    }
+
+    #[test]
+    fn total_is_sum_of_breakdown() {
+        let mut scoring = Scoring::new();
+        scoring.hit();
+        scoring.kill(RobotType::Boss);
+        scoring.crash(RobotType::Soldier);
+        scoring.finish(10, 90, 3);
+        let sum: i64 = scoring.breakdown().iter().map(|&(_, points)| points)
+                                                                    .sum();
+        assert_eq!(scoring.total(), sum);
+    }
+}
@@ -0,0 +1,690 @@
+//screens of the application
+//
+//Screens are kept in a stack: title screen is at the bottom, menus are
+//pushed over it, the game screen is pushed when the game starts, pause menu
+//and results are pushed over the game, entry of name for high scores is
+//pushed over results, statistics of the game can be shown over them too.
+//Input and rendering go to the top screen, menus over
+//the game are drawn as a panel over the field.
+
+use piston_window::*;
+
+use std::path::PathBuf;
+
+use crate::{App, Difficulty, DIFFICULTIES, draw_text, WIDTH_CELL_SIZE,
+            HEIGHT_CELL_SIZE, TICKS_PER_SECOND};
+use crate::levels::LEVELS;
+use crate::scores::{self, ScoreEntry};
+use crate::theme;
+
+pub const MAX_VOLUME: u32 = 10;
+
+//keys which are used by the application and can't be bound to actions
+const RESERVED_KEYS: [Key; 18] = [Key::Escape, Key::Return, Key::Backspace,
+                                Key::P, Key::N, Key::Z, Key::M,
+                                Key::D1, Key::D2, Key::D3,
+                                Key::F2, Key::F3, Key::F4, Key::F5, Key::F9,
+                                Key::F11, Key::Up, Key::Down];
+
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy)]
+pub enum Screen {
+    Title, LevelSelect, Options, Bindings, HighScores, Statistics,
+    Playing, Pause, Results, NameEntry
+}
+
+//screen in the stack with chosen item of its menu
+pub struct ScreenState {
+    pub screen: Screen,
+    pub selected: usize
+}
+
+//actions of the hero which can be bound to keys
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy)]
+pub enum HeroAction {
+    MoveUp, MoveDown, MoveLeft, MoveRight, Stop,
+    FireUp, FireDown, FireLeft, FireRight
+}
+
+pub const HERO_ACTIONS: [HeroAction; 9] = [
+    HeroAction::MoveUp, HeroAction::MoveDown,
+    HeroAction::MoveLeft, HeroAction::MoveRight, HeroAction::Stop,
+    HeroAction::FireUp, HeroAction::FireDown,
+    HeroAction::FireLeft, HeroAction::FireRight];
+
+//options chosen by player
+pub struct Settings {
+    pub difficulty: Difficulty,
+    pub volume: u32, //volume of sounds from 0 to MAX_VOLUME
+    pub music: u32, //volume of music from 0 to MAX_VOLUME
+    pub theme: Option<PathBuf>, //directory of theme, None - default theme
+    pub color_blind: bool, //theme is based on palette for color blindness
+    pub fullscreen: bool,
+    pub integer_scaling: bool, //logical pixel takes whole count of pixels
+    pub bullets_cancel: bool, //bullets of player and enemies destroy each other
+    pub squad_tactics: bool, //enemies attack together
+    pub bindings: Vec<(HeroAction, Key)>
+}
+
+impl HeroAction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HeroAction::MoveUp => "Move up",
+            HeroAction::MoveDown => "Move down",
+            HeroAction::MoveLeft => "Move left",
+            HeroAction::MoveRight => "Move right",
+            HeroAction::Stop => "Stop on crossroad",
+            HeroAction::FireUp => "Shoot up",
+            HeroAction::FireDown => "Shoot down",
+            HeroAction::FireLeft => "Shoot left",
+            HeroAction::FireRight => "Shoot right"
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            difficulty: Difficulty::Normal,
+            volume: 8,
+            music: 5,
+            theme: None,
+            color_blind: false,
+            fullscreen: false,
+            integer_scaling: false,
+            bullets_cancel: false,
+            squad_tactics: false,
+            bindings: vec![
+                (HeroAction::MoveUp, Key::W),
+                (HeroAction::MoveDown, Key::S),
+                (HeroAction::MoveLeft, Key::A),
+                (HeroAction::MoveRight, Key::D),
+                (HeroAction::Stop, Key::Space),
+                (HeroAction::FireUp, Key::I),
+                (HeroAction::FireDown, Key::K),
+                (HeroAction::FireLeft, Key::J),
+                (HeroAction::FireRight, Key::L)]
+        }
+    }
+}
+
+impl Settings {
+    pub fn effects_volume(&self) -> f32 {
+        (self.volume as f32)/(MAX_VOLUME as f32)
+    }
+
+    pub fn music_volume(&self) -> f32 {
+        (self.music as f32)/(MAX_VOLUME as f32)
+    }
+
+    pub fn key(&self, action: HeroAction) -> Option<Key> {
+        self.bindings.iter()
+            .find(|&&(bound_action, _)| bound_action == action)
+            .map(|&(_, key)| key)
+    }
+
+    pub fn action(&self, key: Key) -> Option<HeroAction> {
+        self.bindings.iter()
+            .find(|&&(_, bound_key)| bound_key == key)
+            .map(|&(action, _)| action)
+    }
+
+    //bind key to action, action which had this key gets previous key
+    pub fn bind(&mut self, action: HeroAction, key: Key) {
+        let previous_key = self.key(action);
+        for binding in &mut self.bindings {
+            if binding.0 == action {
+                binding.1 = key;
+            } else if binding.1 == key {
+                if let Some(previous_key) = previous_key {
+                    binding.1 = previous_key;
+                }
+            }
+        }
+    }
+}
+
+impl App {
+    pub(crate) fn screen(&self) -> Screen {
+        self.screens.last().map_or(Screen::Title, |state| state.screen)
+    }
+
+    //the game is played or its menus are shown over it
+    pub(crate) fn in_game(&self) -> bool {
+        self.screens.iter().any(|state| state.screen == Screen::Playing)
+    }
+
+    fn push(&mut self, screen: Screen) {
+        self.screens.push(ScreenState { screen: screen, selected: 0 });
+    }
+
+    //start current level from the beginning
+    fn start_game(&mut self) {
+        self.screens.truncate(1);
+        self.rewind.clear();
+        self.effects.clear();
+        //loaded game could be played on other difficulty and rules
+        self.game.set_difficulty(self.settings.difficulty);
+        self.game.set_bullets_cancel(self.settings.bullets_cancel);
+        self.game.set_squad_tactics(self.settings.squad_tactics);
+        self.game.create_level();
+        self.camera.reset();
+        self.push(Screen::Playing);
+    }
+
+    fn quit_to_title(&mut self) {
+        self.screens.truncate(1);
+        self.rewind.clear();
+        self.effects.clear();
+        self.rewinding = false;
+    }
+
+    //show pause menu or results over the game according to its state,
+    //name of player is asked over results if they get into high scores
+    pub(crate) fn sync_screens(&mut self) {
+        match self.screen() {
+            Screen::NameEntry | Screen::Statistics => return,
+            _ => {}
+        }
+        if !self.in_game() {
+            return;
+        }
+
+        let wanted = if self.game.game_over {
+            Some(Screen::Results)
+        } else if self.game.paused {
+            Some(Screen::Pause)
+        } else {
+            None
+        };
+        let current = match self.screen() {
+            Screen::Pause | Screen::Results => Some(self.screen()),
+            _ => None
+        };
+
+        if wanted != current {
+            if current.is_some() {
+                self.screens.pop();
+            }
+            if let Some(screen) = wanted {
+                self.push(screen);
+            }
+            if wanted == Some(Screen::Results) && self.new_high_score {
+                self.push(Screen::NameEntry);
+            }
+        }
+    }
+
+    //record result of the ended game with typed name
+    fn save_name(&mut self) {
+        let name = self.player_name.trim().to_string();
+        let name = if name.is_empty() {
+            String::from("Player")
+        } else {
+            name
+        };
+        self.player_name = name.clone();
+
+        let game = &self.game;
+        let entry = ScoreEntry {
+            level: game.level,
+            difficulty: game.difficulty,
+            name: name,
+            score: game.score(),
+            seconds: game.ticks/(TICKS_PER_SECOND as u64),
+            kills: game.point_num,
+            crashes: game.crash_num,
+            date: scores::today()
+        };
+        if let Err(message) = self.scores.add(entry) {
+            self.notify(message);
+        }
+        self.new_high_score = false;
+        self.screens.pop();
+    }
+
+    fn menu_items(&self, screen: Screen) -> Vec<String> {
+        let mut items: Vec<String> = match screen {
+            Screen::Title => vec!["Play", "Level select", "Options",
+                                    "High scores", "Statistics", "Quit"]
+                                .iter().map(|item| item.to_string()).collect(),
+            Screen::LevelSelect => LEVELS.iter()
+                                .map(|level| level.name.to_string()).collect(),
+            Screen::Options => vec![
+                format!("Difficulty: {:?}", self.settings.difficulty),
+                format!("Volume: {}", self.settings.volume),
+                format!("Music: {}", self.settings.music),
+                format!("Theme: {}", self.theme.name),
+                format!("Color blind palette: {}",
+                                            on_off(self.settings.color_blind)),
+                format!("Fullscreen: {}", on_off(self.settings.fullscreen)),
+                format!("Integer scaling: {}",
+                                        on_off(self.settings.integer_scaling)),
+                format!("Bullets cancel: {}",
+                                        on_off(self.settings.bullets_cancel)),
+                format!("Squad tactics: {}",
+                                        on_off(self.settings.squad_tactics)),
+                String::from("Controls")],
+            Screen::HighScores => vec![
+                format!("Level: {}", LEVELS[self.scores_level].name),
+                format!("Difficulty: {:?}", self.scores_difficulty)],
+            Screen::Statistics => vec![],
+            Screen::Bindings => HERO_ACTIONS.iter()
+                .map(|&action| match self.settings.key(action) {
+                    Some(key) => format!("{}: {:?}", action.name(), key),
+                    None => format!("{}: -", action.name())
+                })
+                .chain(Some(String::from("Reset to defaults")))
+                .collect(),
+            Screen::Pause => vec![String::from("Resume"),
+                                    String::from("Restart"),
+                                    String::from("Quit to title")],
+            Screen::Results => {
+                let mut items = vec![String::from("Restart")];
+                if self.game.game_win && self.game.level + 1 < LEVELS.len() {
+                    items.push(String::from("Next level"));
+                }
+                items.push(String::from("Statistics"));
+                items.push(String::from("Quit to title"));
+                items
+            },
+            Screen::NameEntry => vec![format!("{}_", self.player_name)],
+            Screen::Playing => vec![]
+        };
+
+        match screen {
+            Screen::LevelSelect | Screen::Options | Screen::Bindings |
+            Screen::HighScores | Screen::Statistics =>
+                                            items.push(String::from("Back")),
+            _ => {}
+        }
+        items
+    }
+
+    //title, lines of information and color of panel over the game
+    fn menu_header(&self, screen: Screen) -> (String, Vec<String>, [f32; 4]) {
+        const PAUSE_BANNER_COLOR: [f32; 4] = [0.9, 0.9, 0.1, 0.96];
+        const WIN_BANNER_COLOR: [f32; 4] = [0.1, 0.9, 0.1, 0.97];
+        const FAIL_BANNER_COLOR: [f32; 4] = [0.8, 0.1, 0.2, 0.99];
+        const RECORD_BANNER_COLOR: [f32; 4] = [0.3, 0.7, 1.0, 0.97];
+        const STATS_BANNER_COLOR: [f32; 4] = [0.7, 0.7, 0.8, 0.97];
+        const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+
+        let game = &self.game;
+        match screen {
+            Screen::Title => (String::from("CROSSFIRE"),
+                        vec![String::from("Up and Down to choose, \
+                                            Enter to select, Esc to go back")],
+                        BLACK),
+            Screen::LevelSelect => (String::from("Level select"), vec![],
+                                                                    BLACK),
+            Screen::Options => (String::from("Options"),
+                        vec![String::from("Left and Right to change")], BLACK),
+            Screen::Bindings => {
+                let hint = if self.rebinding {
+                    "Press new key, Esc to cancel"
+                } else {
+                    "Enter to change key"
+                };
+                (String::from("Controls"), vec![String::from(hint)], BLACK)
+            },
+            Screen::HighScores => {
+                let table = self.scores.table(self.scores_level,
+                                                    self.scores_difficulty);
+                let mut info = vec![String::from("Left and Right to change")];
+                if table.is_empty() {
+                    info.push(String::from("No results yet"));
+                }
+                for (place, entry) in table.iter().enumerate() {
+                    info.push(format!("{}. {}  {}  {}:{:02}  {}/{}  {}",
+                                place + 1, entry.name, entry.score,
+                                entry.seconds/60, entry.seconds%60,
+                                entry.kills, entry.crashes, entry.date));
+                }
+                (String::from("High scores"), info, BLACK)
+            },
+            //statistics of the ended game over it, of all games on title
+            Screen::Statistics => {
+                if self.in_game() {
+                    (String::from("Statistics of the game"),
+                        game.stats.lines(), STATS_BANNER_COLOR)
+                } else {
+                    (String::from("Statistics"),
+                        self.lifetime.stats.lines(), BLACK)
+                }
+            },
+            Screen::NameEntry => (String::from("NEW HIGH SCORE"),
+                        vec![format!("Score {}", game.score()),
+                            String::from("Type your name, Enter to save, \
+                                                            Esc to skip")],
+                        RECORD_BANNER_COLOR),
+            Screen::Pause => (String::from("PAUSED"),
+                        vec![String::from("N - step the game by one tick")],
+                        PAUSE_BANNER_COLOR),
+            Screen::Results => {
+                let seconds = game.ticks/(TICKS_PER_SECOND as u64);
+                let mut info = vec![
+                    format!("Level {}, time {}:{:02}", game.level_name(),
+                                                    seconds/60, seconds%60),
+                    format!("Kills {}, crashes {}, accuracy {:.0}%",
+                                game.point_num, game.crash_num,
+                                game.stats.accuracy()*100.0)];
+                //points of every source of score, three per line
+                let breakdown = game.scoring.breakdown();
+                for sources in breakdown.chunks(3) {
+                    let parts: Vec<String> = sources.iter()
+                        .map(|(source, points)| format!("{} {}", source,
+                                                                    points))
+                        .collect();
+                    info.push(parts.join(", "));
+                }
+                info.push(format!("Score {}", game.score()));
+                if game.game_win {
+                    (String::from("YOU WIN"), info, WIN_BANNER_COLOR)
+                } else {
+                    (String::from("GAME OVER"), info, FAIL_BANNER_COLOR)
+                }
+            },
+            Screen::Playing => (String::new(), vec![], BLACK)
+        }
+    }
+
+    //processing of buttons on menu screens
+    pub(crate) fn input_menu(&mut self, button: &Button) {
+        let key = match button {
+            Button::Keyboard(key) => *key,
+            _ => return
+        };
+        let screen = self.screen();
+
+        if self.rebinding {
+            self.rebinding = false;
+            let index = self.screens.last().map_or(0, |state| state.selected);
+            if key == Key::Escape {
+                return;
+            }
+            if RESERVED_KEYS.contains(&key) {
+                self.notify(format!("Key {:?} is used by the game", key));
+            } else if let Some(&action) = HERO_ACTIONS.get(index) {
+                self.settings.bind(action, key);
+            }
+            return;
+        }
+
+        if screen == Screen::NameEntry {
+            match key {
+                Key::Return => self.save_name(),
+                Key::Backspace => { self.player_name.pop(); },
+                Key::Escape => {
+                    self.new_high_score = false;
+                    self.screens.pop();
+                },
+                _ => {}
+            }
+            return;
+        }
+
+        let count = self.menu_items(screen).len();
+        let selected = match self.screens.last_mut() {
+            Some(state) => {
+                match key {
+                    Key::Up | Key::W =>
+                        state.selected = (state.selected + count - 1)%count,
+                    Key::Down | Key::S =>
+                        state.selected = (state.selected + 1)%count,
+                    _ => {}
+                }
+                state.selected
+            },
+            None => return
+        };
+
+        match key {
+            Key::Return | Key::Space => self.choose(screen, selected),
+            Key::Left | Key::A => self.adjust(screen, selected, false),
+            Key::Right | Key::D => self.adjust(screen, selected, true),
+            Key::Escape => match screen {
+                Screen::Title => self.quit = true,
+                Screen::Pause => {
+                    self.game.paused = false;
+                    self.sync_screens();
+                },
+                Screen::Results => self.quit_to_title(),
+                _ => { self.screens.pop(); }
+            },
+            Key::P if screen == Screen::Pause => {
+                self.game.paused = false;
+                self.sync_screens();
+            },
+            Key::N if screen == Screen::Pause => {
+                self.advance();
+                self.sync_screens();
+            },
+            _ => {}
+        }
+    }
+
+    //action of chosen item of menu
+    fn choose(&mut self, screen: Screen, selected: usize) {
+        let items = self.menu_items(screen);
+        let item = match items.get(selected) {
+            Some(item) => item.as_str(),
+            None => return
+        };
+
+        match (screen, item) {
+            (_, "Back") => { self.screens.pop(); },
+            (Screen::Title, "Play") => self.start_game(),
+            (Screen::Title, "Level select") => {
+                self.push(Screen::LevelSelect);
+                self.screens.last_mut().unwrap().selected = self.game.level;
+            },
+            (Screen::Title, "Options") => self.push(Screen::Options),
+            (Screen::Title, "High scores") => {
+                self.scores_level = self.game.level;
+                self.scores_difficulty = self.settings.difficulty;
+                self.push(Screen::HighScores);
+            },
+            (Screen::HighScores, _) => self.adjust(screen, selected, true),
+            (_, "Statistics") => self.push(Screen::Statistics),
+            (Screen::Title, "Quit") => self.quit = true,
+            (Screen::LevelSelect, _) => {
+                self.game.set_level(selected);
+                self.start_game();
+            },
+            (Screen::Options, "Controls") => self.push(Screen::Bindings),
+            (Screen::Options, _) => self.adjust(screen, selected, true),
+            (Screen::Bindings, "Reset to defaults") => {
+                self.settings.bindings = Settings::default().bindings;
+            },
+            (Screen::Bindings, _) => self.rebinding = true,
+            (Screen::Pause, "Resume") => {
+                self.game.paused = false;
+                self.sync_screens();
+            },
+            (_, "Restart") => {
+                self.rewind.clear();
+                self.effects.clear();
+                self.game.create_level();
+                self.camera.reset();
+                self.sync_screens();
+            },
+            (Screen::Results, "Next level") => {
+                let level = self.game.level + 1;
+                self.game.set_level(level);
+                self.start_game();
+            },
+            (_, "Quit to title") => self.quit_to_title(),
+            _ => {}
+        }
+    }
+
+    //change value of option
+    fn adjust(&mut self, screen: Screen, selected: usize, forward: bool) {
+        if screen == Screen::HighScores {
+            match selected {
+                0 => self.scores_level = cycle(self.scores_level,
+                                                    LEVELS.len(), forward),
+                1 => {
+                    let current = self.scores_difficulty;
+                    let index = DIFFICULTIES.iter()
+                                .position(|&difficulty| difficulty == current)
+                                .unwrap_or(0);
+                    let index = cycle(index, DIFFICULTIES.len(), forward);
+                    self.scores_difficulty = DIFFICULTIES[index];
+                },
+                _ => {}
+            }
+            return;
+        }
+        if screen != Screen::Options {
+            return;
+        }
+
+        match selected {
+            0 => {
+                let current = self.settings.difficulty;
+                let index = DIFFICULTIES.iter()
+                                .position(|&difficulty| difficulty == current)
+                                .unwrap_or(0);
+                let index = cycle(index, DIFFICULTIES.len(), forward);
+                self.settings.difficulty = DIFFICULTIES[index];
+                self.game.set_difficulty(self.settings.difficulty);
+            },
+            1 => {
+                self.settings.volume = step_volume(self.settings.volume,
+                                                                    forward);
+                self.mixer.set_volume(self.settings.effects_volume(),
+                                            self.settings.music_volume());
+            },
+            2 => {
+                self.settings.music = step_volume(self.settings.music,
+                                                                    forward);
+                self.mixer.set_volume(self.settings.effects_volume(),
+                                            self.settings.music_volume());
+            },
+            3 => {
+                let mut themes = vec![None];
+                themes.extend(theme::available().into_iter().map(Some));
+                let index = themes.iter()
+                                .position(|dir| *dir == self.settings.theme)
+                                .unwrap_or(0);
+                let index = cycle(index, themes.len(), forward);
+                self.set_theme(themes[index].clone(),
+                                                    self.settings.color_blind);
+            },
+            4 => {
+                let dir = self.settings.theme.clone();
+                let color_blind = !self.settings.color_blind;
+                self.set_theme(dir, color_blind);
+            },
+            5 => self.settings.fullscreen = !self.settings.fullscreen,
+            6 => {
+                self.settings.integer_scaling = !self.settings.integer_scaling;
+            },
+            7 => {
+                self.settings.bullets_cancel = !self.settings.bullets_cancel;
+                self.game.set_bullets_cancel(self.settings.bullets_cancel);
+            },
+            8 => {
+                self.settings.squad_tactics = !self.settings.squad_tactics;
+                self.game.set_squad_tactics(self.settings.squad_tactics);
+            },
+            _ => {}
+        }
+    }
+
+    //draw menu of top screen over the whole window or over the game
+    pub(crate) fn render_menu(&mut self, args: &RenderArgs) {
+        const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+        const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+        const YELLOW: [f32; 4] = [1.0, 1.0, 0.0, 1.0];
+
+        use graphics::*;
+
+        let screen = self.screen();
+        let selected = self.screens.last().map_or(0, |state| state.selected);
+        let mut items = self.menu_items(screen);
+        let (title, info, panel_color) = self.menu_header(screen);
+        let over_game = self.in_game();
+        //panel over the game grows with lines of information
+        let panel_height = (40.0 + 28.0 + 18.0*(info.len() as f64) +
+                    24.0*(items.len() as f64 + 1.0)).max(HEIGHT_CELL_SIZE*8.0);
+        if self.rebinding {
+            if let Some(action) = HERO_ACTIONS.get(selected) {
+                items[selected] = format!("{}: ...", action.name());
+            }
+        }
+        let glyphs = &mut self.glyphs;
+        let view = self.view;
+        let zoom = view.pixel_scale();
+
+        self.gl.draw(args.viewport(), |c, gl| {
+            if !over_game {
+                clear(BLACK, gl);
+            }
+            let c = c.trans(view.x, view.y).zoom(view.scale);
+            let (center, mut y, text_color, title_size, item_size, step) =
+                                                                if over_game {
+                let panel = [WIDTH_CELL_SIZE*3.5, HEIGHT_CELL_SIZE*3.0,
+                                WIDTH_CELL_SIZE*10.0, panel_height];
+                rectangle(panel_color, panel, c.transform, gl);
+                (WIDTH_CELL_SIZE*8.5, HEIGHT_CELL_SIZE*3.0 + 40.0, BLACK,
+                                                            28, 16, 24.0)
+            } else {
+                let (item_size, step) = if items.len() > 7 {
+                    (16, 24.0)
+                } else {
+                    (20, 34.0)
+                };
+                (WIDTH_CELL_SIZE*10.0, 96.0, WHITE, 32, item_size, step)
+            };
+
+            draw_text(&title, title_size, text_color, center, y, true,
+                                                    zoom, glyphs, &c, gl);
+            y += 28.0;
+            for line in &info {
+                draw_text(line, 12, text_color, center, y, true,
+                                                    zoom, glyphs, &c, gl);
+                y += 18.0;
+            }
+
+            y += step;
+            for (i, item) in items.iter().enumerate() {
+                let (text, color) = if i == selected {
+                    let color = if over_game { text_color } else { YELLOW };
+                    (format!("> {} <", item), color)
+                } else {
+                    (item.clone(), text_color)
+                };
+                draw_text(&text, item_size, color, center, y, true,
+                                                    zoom, glyphs, &c, gl);
+                y += step;
+            }
+        });
+    }
+}
+
+fn step_volume(volume: u32, forward: bool) -> u32 {
+    if forward {
+        (volume + 1).min(MAX_VOLUME)
+    } else {
+        volume.saturating_sub(1)
+    }
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value { "On" } else { "Off" }
+}
+
+//next or previous index in list of count values
+fn cycle(index: usize, count: usize, forward: bool) -> usize {
+    if forward {
+        (index + 1)%count
+    } else {
+        (index + count - 1)%count
+    }
+}
@@ -0,0 +1,467 @@
+//snapshots of the game state
+//
+//Snapshot keeps full state of the game: landscape, robots with their
+//behaviour states, all bullets, counters and state of random generator,
+//so the game continues exactly after restoring. Snapshot can be saved
+//to a text file and loaded back, e.g. as save slot or to catch a bug.
+
+use std::fs;
+use std::path::Path;
+
+use crate::{Block, Bullet, DeathCause, Difficulty, EnimyState, Game,
+            GameObject, Robot, RobotType, DIFFICULTIES};
+use crate::levels::LEVELS;
+use crate::records::{variant, Words, BLOCK_TYPES, DEATH_CAUSES, DIRECTS,
+                    ENIMY_STATES, ROBOT_TYPES};
+use crate::rng::GameRng;
+use crate::scoring::Scoring;
+use crate::stats::GameStats;
+
+const HEADER: &str = "crossfire-snapshot 1";
+
+#[derive(Clone)]
+pub struct Snapshot {
+    hero: Robot,
+    blocks: Vec<Block>,
+    enimies: Vec<Robot>,
+    free_bullets: Vec<Bullet>,
+    point_num: i64,
+    crash_num: i64,
+    scoring: Scoring,
+    stats: GameStats,
+    ticks: u64,
+    level: usize,
+    difficulty: Difficulty,
+    boss_pending: bool,
+    death_causes: Vec<DeathCause>,
+    hero_respawn: u32,
+    squad_tactics: bool,
+    bullets_cancel: bool,
+    paused: bool,
+    game_over: bool,
+    game_win: bool,
+    rng_state: u64
+}
+
+impl Game {
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            hero: self.hero.clone(),
+            blocks: self.blocks.clone(),
+            enimies: self.enimies.clone(),
+            free_bullets: self.free_bullets.clone(),
+            point_num: self.point_num,
+            crash_num: self.crash_num,
+            scoring: self.scoring,
+            stats: self.stats.clone(),
+            ticks: self.ticks,
+            level: self.level,
+            difficulty: self.difficulty,
+            boss_pending: self.boss_pending,
+            death_causes: self.death_causes.clone(),
+            hero_respawn: self.hero_respawn,
+            squad_tactics: self.squad_tactics,
+            bullets_cancel: self.bullets_cancel,
+            paused: self.paused,
+            game_over: self.game_over,
+            game_win: self.game_win,
+            rng_state: self.rng.state()
+        }
+    }
+
+    //return the game to the state of snapshot,
+    //controller of hero is kept as it is
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.hero = snapshot.hero.clone();
+        self.blocks = snapshot.blocks.clone();
+        self.enimies = snapshot.enimies.clone();
+        self.free_bullets = snapshot.free_bullets.clone();
+        self.point_num = snapshot.point_num;
+        self.crash_num = snapshot.crash_num;
+        self.scoring = snapshot.scoring;
+        self.stats = snapshot.stats.clone();
+        self.ticks = snapshot.ticks;
+        self.level = snapshot.level;
+        self.difficulty = snapshot.difficulty;
+        self.boss_pending = snapshot.boss_pending;
+        self.death_causes = snapshot.death_causes.clone();
+        self.hero_respawn = snapshot.hero_respawn;
+        self.paused = snapshot.paused;
+        self.game_over = snapshot.game_over;
+        self.game_win = snapshot.game_win;
+        self.rng = GameRng::from_state(snapshot.rng_state);
+        self.set_bullets_cancel(snapshot.bullets_cancel);
+        if self.squad_tactics != snapshot.squad_tactics {
+            self.set_squad_tactics(snapshot.squad_tactics);
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.snapshot().to_text())
+            .map_err(|error| format!("Can't save game to {}: {}",
+                                                    path.display(), error))
+    }
+
+    pub fn load(&mut self, path: &Path) -> Result<(), String> {
+        let text = fs::read_to_string(path)
+            .map_err(|error| format!("Can't load game from {}: {}",
+                                                    path.display(), error))?;
+        let snapshot = Snapshot::from_text(&text)?;
+        self.restore(&snapshot);
+        Ok(())
+    }
+}
+
+impl Snapshot {
+    //text with one record per line, numbers are written exactly
+    pub fn to_text(&self) -> String {
+        let mut text = String::from(HEADER);
+        text += "\n";
+        text += &format!("counters {} {} {} {} {} {} {} {} {} {}\n",
+                        self.point_num, self.crash_num, self.scoring.total(),
+                        self.boss_pending, self.squad_tactics, self.paused,
+                        self.game_over, self.game_win, self.rng_state,
+                        self.bullets_cancel);
+        text += &format!("time {}\n", self.ticks);
+        text += &format!("level {}\n", self.level);
+        text += &format!("difficulty {:?}\n", self.difficulty);
+        let scoring = &self.scoring;
+        text += &format!("scoring {} {} {} {} {} {} {} {}\n",
+                        scoring.hits, scoring.kills, scoring.crashes,
+                        scoring.combo, scoring.time, scoring.lives,
+                        scoring.chain, scoring.chain_timer);
+        for record in self.stats.records() {
+            text += &format!("stats {}\n", record);
+        }
+
+        let deaths: Vec<String> = self.death_causes.iter()
+                                    .map(|cause| format!(" {:?}", cause))
+                                    .collect();
+        text += &format!("deaths{}\n", deaths.concat());
+        if self.hero_respawn > 0 {
+            text += &format!("respawn {}\n", self.hero_respawn);
+        }
+
+        for block in &self.blocks {
+            text += &format!("block {} {:?}\n",
+                            object_text(&block.object), block.block_type);
+        }
+        robot_text(&mut text, "hero", &self.hero);
+        for enimy in &self.enimies {
+            robot_text(&mut text, "enimy", enimy);
+        }
+        for bullet in &self.free_bullets {
+            text += &format!("free {}\n", bullet_text(bullet));
+        }
+        text
+    }
+
+    pub fn from_text(text: &str) -> Result<Snapshot, String> {
+        let mut lines = text.lines().enumerate();
+        match lines.next() {
+            Some((_, line)) if line == HEADER => {},
+            _ => return Err(String::from("It is not a snapshot of the game"))
+        }
+
+        let mut snapshot = Snapshot {
+            hero: Robot::new(0.0, 0.0, RobotType::Hero, EnimyState::Manual),
+            blocks: vec![], enimies: vec![], free_bullets: vec![],
+            point_num: 0, crash_num: 0, scoring: Scoring::new(),
+            stats: GameStats::new(), ticks: 0, level: 1,
+            difficulty: Difficulty::Normal, boss_pending: false,
+            death_causes: vec![], hero_respawn: 0,
+            squad_tactics: false, bullets_cancel: false, paused: false,
+            game_over: false, game_win: false, rng_state: 1
+        };
+        //bullets are written after robot which has them
+        let mut last_robot: Option<&str> = None;
+
+        for (line_num, line) in lines {
+            let error = |message: String| {
+                format!("Line {} of snapshot: {}", line_num + 1, message)
+            };
+            let mut words = Words(line.split_whitespace());
+
+            match words.next().map_err(&error)? {
+                "counters" => {
+                    snapshot.point_num = words.parse().map_err(&error)?;
+                    snapshot.crash_num = words.parse().map_err(&error)?;
+                    //snapshots without record of scoring keep the whole
+                    //score as points of kills
+                    snapshot.scoring = Scoring::new();
+                    snapshot.scoring.kills = words.parse().map_err(&error)?;
+                    snapshot.boss_pending = words.parse().map_err(&error)?;
+                    snapshot.squad_tactics = words.parse().map_err(&error)?;
+                    snapshot.paused = words.parse().map_err(&error)?;
+                    snapshot.game_over = words.parse().map_err(&error)?;
+                    snapshot.game_win = words.parse().map_err(&error)?;
+                    snapshot.rng_state = words.parse().map_err(&error)?;
+                    //bullets don't cancel each other in old snapshots
+                    snapshot.bullets_cancel = words.parse_or(false)
+                                                        .map_err(&error)?;
+                },
+                //snapshots without time start the clock from zero
+                "time" => {
+                    snapshot.ticks = words.parse().map_err(&error)?;
+                },
+                //it's written after counters and replaces their score
+                "scoring" => {
+                    let scoring = &mut snapshot.scoring;
+                    scoring.hits = words.parse().map_err(&error)?;
+                    scoring.kills = words.parse().map_err(&error)?;
+                    scoring.crashes = words.parse().map_err(&error)?;
+                    scoring.combo = words.parse().map_err(&error)?;
+                    scoring.time = words.parse().map_err(&error)?;
+                    scoring.lives = words.parse().map_err(&error)?;
+                    scoring.chain = words.parse().map_err(&error)?;
+                    scoring.chain_timer = words.parse().map_err(&error)?;
+                },
+                //snapshots without statistics start them from zero
+                "stats" => {
+                    let name = words.next().map_err(&error)?;
+                    snapshot.stats.read(name, &mut words).map_err(&error)?;
+                },
+                "level" => {
+                    let level: usize = words.parse().map_err(&error)?;
+                    if level >= LEVELS.len() {
+                        return Err(error(format!("unknown level {}", level)));
+                    }
+                    snapshot.level = level;
+                },
+                //snapshots without difficulty are played on normal one
+                "difficulty" => {
+                    snapshot.difficulty = words.variant(&DIFFICULTIES)
+                                                        .map_err(&error)?;
+                },
+                "deaths" => {
+                    while let Ok(word) = words.next() {
+                        let cause = variant(word, &DEATH_CAUSES)
+                                                        .map_err(&error)?;
+                        snapshot.death_causes.push(cause);
+                    }
+                },
+                //hero is on the field if there is no respawn
+                "respawn" => {
+                    snapshot.hero_respawn = words.parse().map_err(&error)?;
+                },
+                "block" => {
+                    let object = object(&mut words).map_err(&error)?;
+                    let block_type = words.variant(&BLOCK_TYPES)
+                                                        .map_err(&error)?;
+                    snapshot.blocks.push(Block { object: object,
+                                                block_type: block_type });
+                },
+                "hero" => {
+                    snapshot.hero = robot(&mut words).map_err(&error)?;
+                    last_robot = Some("hero");
+                },
+                "enimy" => {
+                    snapshot.enimies.push(robot(&mut words)
+                                                        .map_err(&error)?);
+                    last_robot = Some("enimy");
+                },
+                "bullet" => {
+                    let bullet = bullet(&mut words).map_err(&error)?;
+                    let robot = match last_robot {
+                        Some("hero") => Some(&mut snapshot.hero),
+                        Some(_) => snapshot.enimies.last_mut(),
+                        None => None
+                    };
+                    match robot {
+                        Some(robot) => robot.bullets.push(bullet),
+                        None => return Err(error(String::from(
+                                                "bullet without robot")))
+                    }
+                },
+                "free" => {
+                    snapshot.free_bullets.push(bullet(&mut words)
+                                                        .map_err(&error)?);
+                },
+                record => return Err(error(format!("unknown record '{}'",
+                                                                    record)))
+            }
+        }
+
+        Ok(snapshot)
+    }
+}
+
+fn object_text(object: &GameObject) -> String {
+    format!("{} {} {} {}", object.x, object.y, object.width, object.height)
+}
+
+fn bullet_text(bullet: &Bullet) -> String {
+    format!("{} {:?}", object_text(&bullet.object), bullet.direct)
+}
+
+fn robot_text(text: &mut String, record: &str, robot: &Robot) {
+    *text += &format!("{} {:?} {} {} {} {:?} {:?} {} {} {} {} {:?} {:?} {} {} \
+                        {}\n",
+                    record, robot.robot_type, object_text(&robot.object),
+                    robot.start_x, robot.start_y,
+                    robot.direct, robot.next_direct, robot.speed,
+                    robot.lives, robot.bullet_speed, robot.max_bullets,
+                    robot.prepare_fire, robot.action_state,
+                    robot.hit_flash, robot.invulnerable, robot.lured);
+    for bullet in &robot.bullets {
+        *text += &format!("bullet {}\n", bullet_text(bullet));
+    }
+}
+
+fn object(words: &mut Words) -> Result<GameObject, String> {
+    Ok(GameObject {
+        x: words.parse()?,
+        y: words.parse()?,
+        width: words.parse()?,
+        height: words.parse()?
+    })
+}
+
+fn bullet(words: &mut Words) -> Result<Bullet, String> {
+    let object = object(words)?;
+    let direct = words.variant(&DIRECTS)?;
+    Ok(Bullet::new(object.x, object.y, object.width, object.height, direct))
+}
+
+fn robot(words: &mut Words) -> Result<Robot, String> {
+    let robot_type = words.variant(&ROBOT_TYPES)?;
+    let object = object(words)?;
+    let mut robot = Robot::new(0.0, 0.0, robot_type, EnimyState::Manual);
+    robot.object = object;
+    robot.start_x = words.parse()?;
+    robot.start_y = words.parse()?;
+    robot.direct = words.variant(&DIRECTS)?;
+    robot.next_direct = words.variant(&DIRECTS)?;
+    robot.speed = words.parse()?;
+    robot.lives = words.parse()?;
+    robot.bullet_speed = words.parse()?;
+    robot.max_bullets = words.parse()?;
+    robot.prepare_fire = words.variant(&DIRECTS)?;
+    robot.action_state = words.variant(&ENIMY_STATES)?;
+    robot.hit_flash = words.parse()?;
+    robot.invulnerable = words.parse()?;
+    //robots of snapshots without it are not lured
+    robot.lured = words.parse_or(0)?;
+    Ok(robot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::HunterBot;
+
+    //game in the middle of the fight, with bullets and damaged robots
+    fn played_game(ticks: usize) -> Game {
+        let mut game = Game::new();
+        game.set_level(0);
+        game.create_level();
+        game.reseed(7);
+        game.set_hero_controller(Some(Box::new(HunterBot)));
+        for _ in 0..ticks {
+            game.step();
+        }
+        game
+    }
+
+    #[test]
+    fn text_restores_equal_game() {
+        let game = played_game(600);
+        let text = game.snapshot().to_text();
+
+        let mut restored = Game::new();
+        restored.restore(&Snapshot::from_text(&text).unwrap());
+        assert_eq!(restored.snapshot().to_text(), text);
+    }
+
+    #[test]
+    fn restored_game_continues_the_same() {
+        let mut game = played_game(600);
+        assert!(!game.game_over);
+        let snapshot = Snapshot::from_text(&game.snapshot().to_text())
+                                                                .unwrap();
+        let mut restored = Game::new();
+        restored.set_hero_controller(Some(Box::new(HunterBot)));
+        restored.restore(&snapshot);
+
+        for _ in 0..600 {
+            game.step();
+            restored.step();
+        }
+        assert_eq!(restored.snapshot().to_text(), game.snapshot().to_text());
+    }
+
+    #[test]
+    fn old_text_without_optional_records() {
+        let game = played_game(600);
+        //snapshots before time, level, difficulty, scoring, statistics,
+        //respawn, luring of robots and cancelling of bullets
+        let old: Vec<String> = game.snapshot().to_text().lines()
+            .filter(|line| {
+                let name = line.split_whitespace().next().unwrap_or("");
+                !["time", "level", "difficulty", "scoring", "stats",
+                    "respawn"].contains(&name)
+            })
+            .map(|line| {
+                if line.starts_with("hero ") || line.starts_with("enimy ")
+                    || line.starts_with("counters ") {
+                    let mut words: Vec<&str> = line.split(' ').collect();
+                    words.pop();
+                    words.join(" ")
+                } else {
+                    String::from(line)
+                }
+            })
+            .collect();
+
+        let snapshot = Snapshot::from_text(&old.join("\n")).unwrap();
+        assert_eq!(snapshot.ticks, 0);
+        assert_eq!(snapshot.level, 1);
+        assert_eq!(snapshot.difficulty, Difficulty::Normal);
+        assert_eq!(snapshot.hero_respawn, 0);
+        assert_eq!(snapshot.scoring.total(), game.score());
+        assert_eq!(snapshot.stats.shots, 0);
+        assert_eq!(snapshot.hero.lured, 0);
+        assert!(!snapshot.bullets_cancel);
+        assert!(snapshot.enimies.iter().all(|enimy| enimy.lured == 0));
+        assert_eq!(snapshot.enimies.len(), game.enimies.len());
+        assert_eq!(snapshot.blocks.len(), game.blocks.len());
+    }
+
+    #[test]
+    fn difficulty_is_restored() {
+        let mut game = Game::new();
+        game.set_difficulty(Difficulty::Hard);
+        game.create_level();
+        let text = game.snapshot().to_text();
+
+        let mut restored = Game::new();
+        restored.set_difficulty(Difficulty::Easy);
+        restored.restore(&Snapshot::from_text(&text).unwrap());
+        assert_eq!(restored.difficulty, Difficulty::Hard);
+        assert_eq!(restored.hero.lives, Difficulty::Hard.hero_lives());
+    }
+
+    #[test]
+    fn rules_are_restored() {
+        let mut game = played_game(0);
+        game.set_bullets_cancel(true);
+        game.set_squad_tactics(true);
+        let text = game.snapshot().to_text();
+
+        let mut restored = Game::new();
+        restored.restore(&Snapshot::from_text(&text).unwrap());
+        assert!(restored.bullets_cancel);
+        assert!(restored.squad_tactics);
+
+        game.set_bullets_cancel(false);
+        game.set_squad_tactics(false);
+        restored.restore(&game.snapshot());
+        assert!(!restored.bullets_cancel);
+        assert!(!restored.squad_tactics);
+    }
+
+    #[test]
+    fn wrong_header_is_error() {
+        assert!(Snapshot::from_text("crossfire statistics 1\n").is_err());
+    }
+}
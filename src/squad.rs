@@ -0,0 +1,351 @@
+//squad-level planner for attacking enemies
+//
+//Classic enemies decide independently and often crash into each other.
+//Squad controller makes the same decisions as classic one, but it gives
+//every attacking enemy a role, makes decisions for them on every crossroad
+//and reserves cells, so two enemies never try to enter the same cell.
+
+use crate::{Block, BlockType, Direct, EnimyState, Robot,
+            WIDTH_CELL_SIZE, HEIGHT_CELL_SIZE};
+use crate::ai::ClassicAi;
+use crate::controller::{Command, Controller, GameView};
+use crate::rng::GameRng;
+
+//roles of attacking enemies in the squad
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy)]
+pub enum SquadRole {
+    Chaser, //goes directly to the hero
+    Flanker, //comes to the hero from the side opposite to the chaser
+    Blocker //takes the hero's row or column and holds it under fire
+}
+
+//controller of enemies acting as a squad, built on the classic one
+pub struct SquadAi {
+    classic: ClassicAi,
+    pub max_attackers: usize, //limit of enemies attacking at the same time
+}
+
+//cell of landscape as column and row
+type Cell = (i64, i64);
+
+impl SquadAi {
+    pub fn new(max_attackers: usize) -> SquadAi {
+        SquadAi { classic: ClassicAi::new(), max_attackers: max_attackers }
+    }
+
+    //hold back enemies going to attack if too many of them already attack
+    fn limit_attackers(&self, enimies: &[Robot], commands: &mut [Command]) {
+        let mut attackers = enimies.iter()
+                .filter(|enimy| enimy.action_state == EnimyState::Attack)
+                .count();
+
+        for (enimy, command) in enimies.iter().zip(commands.iter_mut()) {
+            let starts_attack = command.state == EnimyState::Attack &&
+                                    enimy.action_state != EnimyState::Attack;
+            if !starts_attack {
+                continue;
+            }
+
+            if attackers < self.max_attackers {
+                attackers += 1;
+            } else {
+                command.direct = enimy.next_direct;
+                command.state = enimy.action_state;
+            }
+        }
+    }
+
+    //give roles for all attackers, nearest to the hero is the chaser
+    pub fn roles(&self, enimies: &[Robot], commands: &[Command], hero: &Robot)
+                                        -> Vec<(usize, SquadRole)> {
+        let hero_cell = robot_cell(hero);
+        let mut attackers: Vec<(usize, i64)> = enimies.iter().enumerate()
+                .filter(|&(num, _)| commands[num].state == EnimyState::Attack)
+                .map(|(num, enimy)| (num, distance(robot_cell(enimy),
+                                                                hero_cell)))
+                .collect();
+        attackers.sort_by_key(|&(num, dist)| (dist, num));
+
+        attackers.iter().enumerate().map(|(order, &(num, _))| {
+            let role = match order {
+                0 => SquadRole::Chaser,
+                1 => SquadRole::Flanker,
+                _ => SquadRole::Blocker
+            };
+            (num, role)
+        }).collect()
+    }
+
+    //choose next direction for attackers standing exactly on a cell
+    fn plan(&self, enimies: &[Robot], commands: &mut [Command],
+                                        blocks: &[Block], hero: &Robot) {
+        let grid = Grid::new(blocks);
+        let hero_cell = robot_cell(hero);
+        let roles = self.roles(enimies, commands, hero);
+
+        let chaser_cell = roles.iter()
+                .find(|&&(_, role)| role == SquadRole::Chaser)
+                .map(|&(num, _)| robot_cell(&enimies[num]));
+
+        //cells of all enemies, and next cells of enemies that are not going
+        //to decide now, so no one enters cell of enemy which stays
+        let mut reserved: Vec<Cell> = vec![];
+        for (enimy, command) in enimies.iter().zip(commands.iter()) {
+            reserved.push(robot_cell(enimy));
+            let deciding = command.state == EnimyState::Attack &&
+                                                    aligned(enimy);
+            if !deciding {
+                reserved.push(step(robot_cell(enimy), enimy.direct));
+            }
+        }
+
+        for (num, role) in roles {
+            let enimy = &enimies[num];
+            if !aligned(enimy) {
+                continue;
+            }
+
+            let cell = robot_cell(enimy);
+            let target = match role {
+                SquadRole::Chaser => hero_cell,
+                SquadRole::Flanker => match chaser_cell {
+                    Some(chaser) => grid.clamp(
+                            (2*hero_cell.0 - chaser.0,
+                             2*hero_cell.1 - chaser.1)),
+                    None => hero_cell
+                },
+                SquadRole::Blocker => {
+                    //nearest cell on the same row or column with the hero
+                    let row_cell = (cell.0, hero_cell.1);
+                    let column_cell = (hero_cell.0, cell.1);
+                    if distance(cell, row_cell) <= distance(cell, column_cell) {
+                        row_cell
+                    } else {
+                        column_cell
+                    }
+                }
+            };
+
+            let new_direct = choose_direct(cell, target, enimy.direct,
+                                            &grid, &reserved);
+            commands[num].direct = new_direct;
+            reserved.push(step(cell, new_direct));
+        }
+    }
+}
+
+impl Controller for SquadAi {
+    fn decide(&mut self, view: &GameView, robots: &[Robot],
+                                    rng: &mut GameRng) -> Vec<Command> {
+        let mut commands = self.classic.decide(view, robots, rng);
+        self.limit_attackers(robots, &mut commands);
+        self.plan(robots, &mut commands, view.blocks, view.hero);
+        commands
+    }
+}
+
+//landscape as table of cells for fast search of block under cell
+struct Grid {
+    width: i64,
+    height: i64,
+    cells: Vec<BlockType>
+}
+
+impl Grid {
+    fn new(blocks: &[Block]) -> Grid {
+        let mut width = 0;
+        let mut height = 0;
+        for block in blocks {
+            let (column, row) = object_cell(block.object.x, block.object.y);
+            width = width.max(column + 1);
+            height = height.max(row + 1);
+        }
+
+        let mut cells = vec![BlockType::WALL; (width*height) as usize];
+        for block in blocks {
+            let (column, row) = object_cell(block.object.x, block.object.y);
+            cells[(row*width + column) as usize] = block.block_type;
+        }
+
+        Grid { width: width, height: height, cells: cells }
+    }
+
+    //enemies can fly over all blocks except walls
+    fn passable(&self, (column, row): Cell) -> bool {
+        if column < 0 || row < 0 || column >= self.width || row >= self.height {
+            return false;
+        }
+        self.cells[(row*self.width + column) as usize] != BlockType::WALL
+    }
+
+    fn clamp(&self, (column, row): Cell) -> Cell {
+        (column.max(0).min(self.width - 1), row.max(0).min(self.height - 1))
+    }
+}
+
+fn object_cell(x: f64, y: f64) -> Cell {
+    ((x/WIDTH_CELL_SIZE).round() as i64, (y/HEIGHT_CELL_SIZE).round() as i64)
+}
+
+fn robot_cell(robot: &Robot) -> Cell {
+    object_cell(robot.object.x, robot.object.y)
+}
+
+//robot stands exactly on a cell and can change direction
+fn aligned(robot: &Robot) -> bool {
+    robot.object.x % WIDTH_CELL_SIZE == 0.0 &&
+        robot.object.y % HEIGHT_CELL_SIZE == 0.0
+}
+
+fn step((column, row): Cell, direct: Direct) -> Cell {
+    match direct {
+        Direct::LEFT => (column - 1, row),
+        Direct::RIGHT => (column + 1, row),
+        Direct::UP => (column, row - 1),
+        Direct::DOWN => (column, row + 1),
+        Direct::NONE => (column, row)
+    }
+}
+
+fn distance(first: Cell, second: Cell) -> i64 {
+    (first.0 - second.0).abs() + (first.1 - second.1).abs()
+}
+
+fn opposite(direct: Direct) -> Direct {
+    match direct {
+        Direct::LEFT => Direct::RIGHT,
+        Direct::RIGHT => Direct::LEFT,
+        Direct::UP => Direct::DOWN,
+        Direct::DOWN => Direct::UP,
+        Direct::NONE => Direct::NONE
+    }
+}
+
+//greedy step to the target over free cells, turning back only if nothing else
+fn choose_direct(cell: Cell, target: Cell, direct: Direct, grid: &Grid,
+                                            reserved: &Vec<Cell>) -> Direct {
+    if cell == target {
+        return Direct::NONE;
+    }
+
+    let mut best = Direct::NONE;
+    let mut best_score = std::i64::MAX;
+    for &candidate in &[Direct::UP, Direct::DOWN, Direct::LEFT, Direct::RIGHT] {
+        let next = step(cell, candidate);
+        if !grid.passable(next) || reserved.contains(&next) {
+            continue;
+        }
+
+        let mut score = distance(next, target)*4;
+        if candidate == opposite(direct) {
+            score += 8;
+        }
+        if candidate == direct {
+            score -= 1;
+        }
+
+        if score < best_score {
+            best_score = score;
+            best = candidate;
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RobotType;
+
+    const SIZE: i64 = 11;
+
+    //open field surrounded by walls
+    fn field() -> Vec<Block> {
+        let mut blocks = vec![];
+        for column in 0..SIZE {
+            for row in 0..SIZE {
+                let border = column == 0 || row == 0 ||
+                                column == SIZE - 1 || row == SIZE - 1;
+                let block_type = if border {
+                    BlockType::WALL
+                } else {
+                    BlockType::NODE
+                };
+                blocks.push(Block::new((column as f64)*WIDTH_CELL_SIZE,
+                                        (row as f64)*HEIGHT_CELL_SIZE,
+                                        WIDTH_CELL_SIZE, HEIGHT_CELL_SIZE,
+                                        block_type));
+            }
+        }
+        blocks
+    }
+
+    fn robot((column, row): Cell, robot_type: RobotType,
+                                        state: EnimyState) -> Robot {
+        Robot::new((column as f64)*WIDTH_CELL_SIZE,
+                    (row as f64)*HEIGHT_CELL_SIZE, robot_type, state)
+    }
+
+    //attackers step cell by cell as planner says,
+    //return cells of them after every step
+    fn converge(starts: &[Cell], hero: Cell, steps: usize) -> Vec<Vec<Cell>> {
+        let squad = SquadAi::new(starts.len());
+        let blocks = field();
+        let grid = Grid::new(&blocks);
+        let hero = robot(hero, RobotType::Hero, EnimyState::Manual);
+        let mut enimies: Vec<Robot> = starts.iter()
+            .map(|&cell| robot(cell, RobotType::Soldier, EnimyState::Attack))
+            .collect();
+
+        let mut history = vec![];
+        for _ in 0..steps {
+            let mut commands: Vec<Command> = enimies.iter()
+                                    .map(|enimy| Command::keep(enimy))
+                                    .collect();
+            squad.plan(&enimies, &mut commands, &blocks, &hero);
+            for (enimy, command) in enimies.iter_mut().zip(&commands) {
+                enimy.direct = command.direct;
+                let next = step(robot_cell(enimy), command.direct);
+                if grid.passable(next) {
+                    enimy.object.x = (next.0 as f64)*WIDTH_CELL_SIZE;
+                    enimy.object.y = (next.1 as f64)*HEIGHT_CELL_SIZE;
+                }
+            }
+            history.push(enimies.iter().map(robot_cell).collect());
+        }
+        history
+    }
+
+    fn shared(cells: &[Cell]) -> bool {
+        cells.iter().enumerate()
+            .any(|(num, cell)| cells[num + 1..].contains(cell))
+    }
+
+    #[test]
+    fn two_attackers_never_share_cell() {
+        let hero = (5, 5);
+        for &starts in &[[(1, 5), (9, 5)], [(2, 2), (3, 1)],
+                         [(5, 1), (5, 9)], [(1, 1), (2, 2)]] {
+            let history = converge(&starts, hero, 12);
+            for cells in &history {
+                assert!(!shared(cells), "{:?} from {:?}", cells, starts);
+            }
+            //both of them come next to the hero
+            let last = history.last().unwrap();
+            assert!(last.iter().all(|&cell| distance(cell, hero) <= 2),
+                    "{:?} from {:?}", last, starts);
+        }
+    }
+
+    #[test]
+    fn attacker_doesnt_enter_cell_of_standing_one() {
+        //chaser stops on the hero, then the farther of two others becomes
+        //the nearest one and decides first, while another one stands still
+        let history = converge(&[(2, 1), (5, 6), (5, 4)], (5, 5), 12);
+        for cells in &history {
+            assert!(!shared(cells), "{:?}", cells);
+        }
+    }
+}
@@ -0,0 +1,382 @@
+//statistics of games
+//
+//Statistics of the game are collected from its events and state of hero
+//every tick: shots and hits of hero, lives lost by every cause, time spent
+//standing, moving over crossroads and sliding in passages, and enemies killed
+//in every state of their behaviour. Lifetime statistics sum all finished
+//games and are kept in the file in data directory of the user, near high
+//scores. Games of practice mode are not added to them.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{DeathCause, EnimyState, RobotType, TICKS_PER_SECOND};
+use crate::events::{Damager, GameEvent};
+use crate::scores::data_dir;
+use crate::records::{Words, DEATH_CAUSES, ENIMY_STATES};
+
+const HEADER: &str = "crossfire statistics 1";
+const FILE_NAME: &str = "stats.txt";
+
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct GameStats {
+    pub games: u64, //finished games
+    pub wins: u64,
+    pub ticks: u64,
+    pub shots: u64, //bullets launched by hero
+    pub hits: u64, //bullets of hero which damaged enemies
+    pub standing_ticks: u64,
+    pub moving_ticks: u64, //moving over crossroads
+    pub sliding_ticks: u64, //moving in passages
+    pub deaths: Vec<(DeathCause, u64)>, //lives lost by causes
+    pub kills: Vec<(EnimyState, u64)> //enemies destroyed in states
+}
+
+impl GameStats {
+    pub fn new() -> GameStats {
+        GameStats {
+            games: 0, wins: 0, ticks: 0, shots: 0, hits: 0,
+            standing_ticks: 0, moving_ticks: 0, sliding_ticks: 0,
+            deaths: vec![], kills: vec![]
+        }
+    }
+
+    //event of the game
+    pub fn event(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::ShotFired { robot_type: RobotType::Hero, .. } =>
+                                                            self.shots += 1,
+            GameEvent::EnemyHit { by: Damager::HeroBullet, .. } =>
+                                                            self.hits += 1,
+            GameEvent::EnemyKilled { by, state, .. } => {
+                if by == Damager::HeroBullet {
+                    self.hits += 1;
+                }
+                count(&mut self.kills, state, 1);
+            },
+            GameEvent::HeroHit { cause, .. } => count(&mut self.deaths,
+                                                                    cause, 1),
+            GameEvent::LevelWon => {
+                self.games += 1;
+                self.wins += 1;
+            },
+            GameEvent::LevelFailed => self.games += 1,
+            _ => {}
+        }
+    }
+
+    //bullets of hero which damaged enemies, from 0.0 to 1.0
+    pub fn accuracy(&self) -> f64 {
+        if self.shots == 0 {
+            0.0
+        } else {
+            ((self.hits as f64)/(self.shots as f64)).min(1.0)
+        }
+    }
+
+    //add statistics of another game
+    pub fn add(&mut self, other: &GameStats) {
+        self.games += other.games;
+        self.wins += other.wins;
+        self.ticks += other.ticks;
+        self.shots += other.shots;
+        self.hits += other.hits;
+        self.standing_ticks += other.standing_ticks;
+        self.moving_ticks += other.moving_ticks;
+        self.sliding_ticks += other.sliding_ticks;
+        for &(cause, number) in &other.deaths {
+            count(&mut self.deaths, cause, number);
+        }
+        for &(state, number) in &other.kills {
+            count(&mut self.kills, state, number);
+        }
+    }
+
+    //lines of text for screen of statistics
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("Games {}, wins {}, time {}", self.games, self.wins,
+                                                        clock(self.ticks)),
+            format!("Shots {}, hits {}, accuracy {:.0}%", self.shots,
+                                            self.hits, self.accuracy()*100.0),
+            format!("Standing {}, moving {}, sliding {}",
+                    clock(self.standing_ticks), clock(self.moving_ticks),
+                    clock(self.sliding_ticks))];
+
+        if self.deaths.is_empty() {
+            lines.push(String::from("Lives lost: none"));
+        } else {
+            lines.push(String::from("Lives lost by causes:"));
+            lines.extend(pairs(&self.deaths));
+        }
+        if self.kills.is_empty() {
+            lines.push(String::from("Kills: none"));
+        } else {
+            lines.push(String::from("Kills by states of enemies:"));
+            lines.extend(pairs(&self.kills));
+        }
+        lines
+    }
+
+    //records of text, one per line, with name at the start
+    pub fn records(&self) -> Vec<String> {
+        let mut records = vec![
+            format!("games {} {}", self.games, self.wins),
+            format!("shots {} {}", self.shots, self.hits),
+            format!("ticks {} {} {} {}", self.ticks, self.standing_ticks,
+                                    self.moving_ticks, self.sliding_ticks)];
+        for &(cause, number) in &self.deaths {
+            records.push(format!("death {:?} {}", cause, number));
+        }
+        for &(state, number) in &self.kills {
+            records.push(format!("kill {:?} {}", state, number));
+        }
+        records
+    }
+
+    //read record, its name is already read from words
+    pub(crate) fn read(&mut self, name: &str, words: &mut Words)
+                                                    -> Result<(), String> {
+        match name {
+            "games" => {
+                self.games = words.parse()?;
+                self.wins = words.parse()?;
+            },
+            "shots" => {
+                self.shots = words.parse()?;
+                self.hits = words.parse()?;
+            },
+            "ticks" => {
+                self.ticks = words.parse()?;
+                self.standing_ticks = words.parse()?;
+                self.moving_ticks = words.parse()?;
+                self.sliding_ticks = words.parse()?;
+            },
+            "death" => {
+                let cause = words.variant(&DEATH_CAUSES)?;
+                count(&mut self.deaths, cause, words.parse()?);
+            },
+            "kill" => {
+                let state = words.variant(&ENIMY_STATES)?;
+                count(&mut self.kills, state, words.parse()?);
+            },
+            _ => return Err(format!("unknown record of statistics '{}'",
+                                                                        name))
+        }
+        Ok(())
+    }
+}
+
+//statistics of all finished games
+pub struct LifetimeStats {
+    path: PathBuf,
+    pub stats: GameStats
+}
+
+impl LifetimeStats {
+    //statistics from the file in data directory of the user
+    pub fn load_default() -> (LifetimeStats, Option<String>) {
+        LifetimeStats::load(&data_dir().join(FILE_NAME))
+    }
+
+    //missing or broken file gives empty statistics,
+    //error of broken file is returned for the player
+    pub fn load(path: &Path) -> (LifetimeStats, Option<String>) {
+        let mut lifetime = LifetimeStats { path: path.to_path_buf(),
+                                            stats: GameStats::new() };
+        if !path.exists() {
+            return (lifetime, None);
+        }
+        let result = fs::read_to_string(path)
+            .map_err(|error| error.to_string())
+            .and_then(|text| from_text(&text));
+        match result {
+            Ok(stats) => {
+                lifetime.stats = stats;
+                (lifetime, None)
+            },
+            Err(message) => (lifetime, Some(format!("Can't load statistics \
+                                    from {}: {}", path.display(), message)))
+        }
+    }
+
+    //add statistics of finished game and write the file
+    pub fn add(&mut self, game: &GameStats) -> Result<(), String> {
+        self.stats.add(game);
+
+        let error = |error: std::io::Error| {
+            format!("Can't save statistics to {}: {}", self.path.display(),
+                                                                        error)
+        };
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(error)?;
+        }
+        fs::write(&self.path, to_text(&self.stats)).map_err(error)
+    }
+}
+
+fn to_text(stats: &GameStats) -> String {
+    let mut text = String::from(HEADER);
+    text += "\n";
+    for record in stats.records() {
+        text += &record;
+        text += "\n";
+    }
+    text
+}
+
+fn from_text(text: &str) -> Result<GameStats, String> {
+    let mut lines = text.lines().enumerate();
+    match lines.next() {
+        Some((_, line)) if line == HEADER => {},
+        _ => return Err(String::from("It is not a file of statistics"))
+    }
+
+    let mut stats = GameStats::new();
+    for (line_num, line) in lines {
+        let error = |message: String| {
+            format!("line {}: {}", line_num + 1, message)
+        };
+        let mut words = Words(line.split_whitespace());
+        let name = words.next().map_err(&error)?;
+        stats.read(name, &mut words).map_err(&error)?;
+    }
+    Ok(stats)
+}
+
+//add number to counter of key
+fn count<T: PartialEq + Copy>(counters: &mut Vec<(T, u64)>, key: T,
+                                                            number: u64) {
+    match counters.iter_mut().find(|(counter_key, _)| *counter_key == key) {
+        Some(counter) => counter.1 += number,
+        None => counters.push((key, number))
+    }
+}
+
+//counters as text, two per line
+fn pairs<T: std::fmt::Debug>(counters: &[(T, u64)]) -> Vec<String> {
+    counters.chunks(2)
+        .map(|chunk| {
+            let parts: Vec<String> = chunk.iter()
+                .map(|(key, number)| format!("{:?} {}", key, number))
+                .collect();
+            parts.join(", ")
+        })
+        .collect()
+}
+
+//ticks as minutes and seconds
+fn clock(ticks: u64) -> String {
+    let seconds = ticks/(TICKS_PER_SECOND as u64);
+    format!("{}:{:02}", seconds/60, seconds%60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Direct;
+
+    fn kill(by: Damager, state: EnimyState) -> GameEvent {
+        GameEvent::EnemyKilled { robot_type: RobotType::Soldier,
+                                    x: 0.0, y: 0.0, by: by, state: state }
+    }
+
+    fn hit(by: Damager) -> GameEvent {
+        GameEvent::EnemyHit { robot_type: RobotType::Tank,
+                                x: 0.0, y: 0.0, by: by }
+    }
+
+    fn shot(robot_type: RobotType) -> GameEvent {
+        GameEvent::ShotFired { robot_type: robot_type, x: 0.0, y: 0.0,
+                                direct: Direct::LEFT }
+    }
+
+    fn death(cause: DeathCause) -> GameEvent {
+        GameEvent::HeroHit { cause: cause, x: 0.0, y: 0.0, lives: 2 }
+    }
+
+    //statistics of one lost game
+    fn played() -> GameStats {
+        let mut stats = GameStats::new();
+        let events = [
+            shot(RobotType::Hero), shot(RobotType::Hero),
+            shot(RobotType::Hero), shot(RobotType::Hero),
+            shot(RobotType::Soldier),
+            hit(Damager::HeroBullet), hit(Damager::Collision),
+            kill(Damager::HeroBullet, EnimyState::Attack),
+            kill(Damager::HeroBullet, EnimyState::Attack),
+            kill(Damager::Collision, EnimyState::WaitLeft),
+            kill(Damager::Hero, EnimyState::BossCharge),
+            death(DeathCause::EnemyBullet), death(DeathCause::Contact),
+            death(DeathCause::EnemyBullet),
+            GameEvent::LevelFailed
+        ];
+        for event in events.iter() {
+            stats.event(event);
+        }
+        stats.ticks = 3000;
+        stats.standing_ticks = 1000;
+        stats.moving_ticks = 1500;
+        stats.sliding_ticks = 500;
+        stats
+    }
+
+    #[test]
+    fn events_are_counted() {
+        let stats = played();
+        assert_eq!(stats.games, 1);
+        assert_eq!(stats.wins, 0);
+        assert_eq!(stats.shots, 4);
+        assert_eq!(stats.hits, 3);
+        assert_eq!(stats.accuracy(), 0.75);
+        assert_eq!(stats.deaths, vec![(DeathCause::EnemyBullet, 2),
+                                        (DeathCause::Contact, 1)]);
+        assert_eq!(stats.kills, vec![(EnimyState::Attack, 2),
+                                        (EnimyState::WaitLeft, 1),
+                                        (EnimyState::BossCharge, 1)]);
+    }
+
+    #[test]
+    fn lifetime_sums_games() {
+        let mut lifetime = played();
+        let mut won = GameStats::new();
+        won.event(&kill(Damager::HeroBullet, EnimyState::WaitLeft));
+        won.event(&GameEvent::LevelWon);
+        lifetime.add(&won);
+
+        assert_eq!(lifetime.games, 2);
+        assert_eq!(lifetime.wins, 1);
+        assert_eq!(lifetime.hits, 4);
+        assert_eq!(lifetime.kills, vec![(EnimyState::Attack, 2),
+                                        (EnimyState::WaitLeft, 2),
+                                        (EnimyState::BossCharge, 1)]);
+    }
+
+    #[test]
+    fn text_restores_equal_stats() {
+        let stats = played();
+        let text = to_text(&stats);
+        let restored = from_text(&text).unwrap();
+        assert_eq!(to_text(&restored), text);
+        assert_eq!(restored.sliding_ticks, 500);
+        assert_eq!(restored.deaths, stats.deaths);
+
+        assert_eq!(to_text(&from_text(HEADER).unwrap()),
+                                            to_text(&GameStats::new()));
+    }
+
+    #[test]
+    fn malformed_text_is_rejected() {
+        let broken = [
+            "crossfire high scores 1\n",
+            "crossfire statistics 1\ngames 1\n",
+            "crossfire statistics 1\ndeath Lava 1\n",
+            "crossfire statistics 1\nkill Attack many\n",
+            "crossfire statistics 1\nwins 1\n"
+        ];
+        for text in broken.iter() {
+            assert!(from_text(text).is_err(), "accepted {:?}", text);
+        }
+    }
+}
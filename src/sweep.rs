@@ -0,0 +1,121 @@
+//swept collision
+//
+//Robots and bullets move by their whole speed every tick, so fast bullet
+//could jump over thin wall or robot, and two objects moving toward each other
+//could pass through one another between two ticks. Instead of checking overlap
+//only at the end of tick, objects are checked along the whole movement.
+//One object is moved in the frame of reference of another, so the path of its
+//corner is segment, and it's tested against rectangle enlarged by the size
+//of the object. Objects are supposed to move in straight lines during tick.
+
+use crate::GameObject;
+
+//time of the first overlap of two rectangles from 0.0 (start of tick)
+//to 1.0 (end of tick), None - they don't overlap during tick;
+//rectangles are given at the end of tick with their positions at the start,
+//touching is not overlap as in HitTestType::INNER
+pub fn hit_time(object: &GameObject, last: (f64, f64),
+                target: &GameObject, target_last: (f64, f64)) -> Option<f64> {
+    //left top corner of object relative to left top corner of target
+    let from = (last.0 - target_last.0, last.1 - target_last.1);
+    let to = (object.x - target.x, object.y - target.y);
+
+    let (x_enter, x_exit) = axis_times(from.0, to.0, -object.width,
+                                                            target.width)?;
+    let (y_enter, y_exit) = axis_times(from.1, to.1, -object.height,
+                                                            target.height)?;
+    let enter = x_enter.max(y_enter).max(0.0);
+    let exit = x_exit.min(y_exit).min(1.0);
+    if enter < exit {
+        Some(enter)
+    } else {
+        None
+    }
+}
+
+//center of object at the time of tick
+pub fn center_at(object: &GameObject, last: (f64, f64),
+                                                time: f64) -> (f64, f64) {
+    let x = last.0 + (object.x - last.0)*time;
+    let y = last.1 + (object.y - last.1)*time;
+    (x + object.width/2.0, y + object.height/2.0)
+}
+
+//interval of time while coordinate moving from start to end of tick
+//is strictly between low and high bounds
+fn axis_times(from: f64, to: f64, low: f64, high: f64) -> Option<(f64, f64)> {
+    let delta = to - from;
+    if delta == 0.0 {
+        if from > low && from < high {
+            Some((std::f64::NEG_INFINITY, std::f64::INFINITY))
+        } else {
+            None
+        }
+    } else {
+        let low_time = (low - from)/delta;
+        let high_time = (high - from)/delta;
+        Some((low_time.min(high_time), low_time.max(high_time)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(x: f64, y: f64, width: f64, height: f64) -> GameObject {
+        GameObject { x: x, y: y, width: width, height: height }
+    }
+
+    #[test]
+    fn fast_bullet_hits_thin_wall() {
+        //bullet jumps from the left of wall to the right of it in one tick
+        let wall = object(100.0, 0.0, 4.0, 32.0);
+        let bullet = object(130.0, 10.0, 8.0, 8.0);
+        let time = hit_time(&bullet, (70.0, 10.0), &wall, (100.0, 0.0));
+        let time = time.expect("bullet passed through wall");
+        assert!((time - 22.0/60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bullets_meet_head_on() {
+        //bullets swap their places, they meet in the middle of tick
+        let left = object(60.0, 0.0, 8.0, 8.0);
+        let right = object(40.0, 0.0, 8.0, 8.0);
+        let time = hit_time(&left, (40.0, 0.0), &right, (60.0, 0.0));
+        let time = time.expect("bullets passed through each other");
+        assert!((time - 0.3).abs() < 1e-9);
+        let (x, y) = center_at(&left, (40.0, 0.0), time);
+        assert!((x - 50.0).abs() < 1e-9 && (y - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn standing_objects_overlap() {
+        let first = object(0.0, 0.0, 32.0, 32.0);
+        let second = object(16.0, 16.0, 32.0, 32.0);
+        assert_eq!(hit_time(&first, (0.0, 0.0), &second, (16.0, 16.0)),
+                                                                    Some(0.0));
+    }
+
+    #[test]
+    fn touching_is_not_hit() {
+        let first = object(0.0, 0.0, 32.0, 32.0);
+        let second = object(32.0, 0.0, 32.0, 32.0);
+        assert_eq!(hit_time(&first, (0.0, 0.0), &second, (32.0, 0.0)), None);
+
+        //moving along the side of another object
+        let moving = object(0.0, 32.0, 32.0, 32.0);
+        assert_eq!(hit_time(&moving, (-40.0, 32.0), &first, (0.0, 0.0)),
+                                                                        None);
+
+        //stopping right at the side
+        assert_eq!(hit_time(&first, (-10.0, 0.0), &second, (32.0, 0.0)),
+                                                                        None);
+    }
+
+    #[test]
+    fn axis_without_movement() {
+        assert!(axis_times(5.0, 5.0, -8.0, 32.0).is_some());
+        assert_eq!(axis_times(-8.0, -8.0, -8.0, 32.0), None);
+        assert_eq!(axis_times(32.0, 32.0, -8.0, 32.0), None);
+    }
+}
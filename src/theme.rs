@@ -0,0 +1,315 @@
+//themes of rendering
+//
+//Theme sets look of every block type, robot type and bullets of every owner:
+//plain color or tile of sprite sheet. Robots can have different tiles for
+//every direction of moving. The default theme is the abstract palette of
+//the game or the palette for color blindness, other themes are directories
+//in THEME_DIR with description in THEME_FILE, looks which are not described
+//there are taken from the palette.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{BlockType, Direct, Robot, RobotType};
+use crate::records::{variant, Words, BLOCK_TYPES, DIRECTS, ROBOT_TYPES};
+
+pub const THEME_DIR: &str = "themes";
+pub const THEME_FILE: &str = "theme.txt";
+
+const HEADER: &str = "crossfire-theme 1";
+
+const BLUE: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+const YELLOW: [f32; 4] = [1.0, 1.0, 0.0, 1.0];
+const DARK_YELLOW: [f32; 4] = [0.6, 0.45, 0.1, 1.0];
+const GR: [f32; 4] = [0.0, 1.0, 1.0, 1.0];
+const LIGHT_GREEN: [f32; 4] = [0.6, 1.0, 0.6, 1.0];
+const GRAY: [f32; 4] = [0.6, 0.6, 0.6, 1.0];
+const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+//colors which are distinguishable with any type of color blindness
+//(palette of Okabe and Ito)
+const CB_ORANGE: [f32; 4] = [0.9, 0.62, 0.0, 1.0];
+const CB_SKY_BLUE: [f32; 4] = [0.34, 0.71, 0.91, 1.0];
+const CB_BLUISH_GREEN: [f32; 4] = [0.0, 0.62, 0.45, 1.0];
+const CB_YELLOW: [f32; 4] = [0.94, 0.89, 0.26, 1.0];
+const CB_BLUE: [f32; 4] = [0.0, 0.45, 0.7, 1.0];
+const CB_VERMILLION: [f32; 4] = [0.84, 0.37, 0.0, 1.0];
+const CB_REDDISH_PURPLE: [f32; 4] = [0.8, 0.47, 0.65, 1.0];
+const CB_DARK_VERMILLION: [f32; 4] = [0.5, 0.2, 0.0, 1.0];
+const CB_PURPLE: [f32; 4] = [0.45, 0.2, 0.6, 1.0];
+const CB_GRAY: [f32; 4] = [0.45, 0.45, 0.45, 1.0];
+
+//owner of bullet
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy)]
+pub enum BulletOwner {
+    Hero, Enimy,
+    Free //bullet of destroyed robot
+}
+
+const BULLET_OWNERS: [BulletOwner; 3] = [BulletOwner::Hero, BulletOwner::Enimy,
+                                            BulletOwner::Free];
+
+//how object is drawn
+#[derive(Debug)]
+#[derive(PartialEq)]
+#[derive(Clone, Copy)]
+pub enum Look {
+    Color([f32; 4]),
+    Tile(u32, u32) //column and row of tile in sprite sheet
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Theme {
+    pub name: String,
+    pub sheet: Option<PathBuf>, //image with tiles
+    pub tile_size: f64, //size of tile in sheet in pixels
+    pub background: [f32; 4],
+    blocks: Vec<(BlockType, Look)>,
+    robots: Vec<(RobotType, Option<Direct>, Look)>, //None - any direction
+    bullets: Vec<(BulletOwner, Look)>
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            name: String::from("Default"),
+            sheet: None,
+            tile_size: 32.0,
+            background: BLACK,
+            blocks: vec![
+                (BlockType::WALL, Look::Color(BLUE)),
+                (BlockType::NODE, Look::Color(YELLOW)),
+                (BlockType::HOLE, Look::Color(GR)),
+                (BlockType::SLIDE, Look::Color(DARK_YELLOW))],
+            robots: ROBOT_TYPES.iter()
+                .map(|&robot_type| (robot_type, None,
+                                    Look::Color(robot_type.stats().color)))
+                .collect(),
+            bullets: vec![
+                (BulletOwner::Hero, Look::Color(LIGHT_GREEN)),
+                (BulletOwner::Enimy, Look::Color(RED)),
+                (BulletOwner::Free, Look::Color(GRAY))]
+        }
+    }
+}
+
+impl Theme {
+    //palette of the default theme for color blindness
+    pub fn color_blind() -> Theme {
+        let robot_color = |robot_type: RobotType| match robot_type {
+            RobotType::Hero => CB_BLUISH_GREEN,
+            RobotType::Soldier => CB_VERMILLION,
+            RobotType::Tank => CB_DARK_VERMILLION,
+            RobotType::Scout => CB_ORANGE,
+            RobotType::Turret => CB_REDDISH_PURPLE,
+            RobotType::Boss => CB_PURPLE
+        };
+
+        Theme {
+            name: String::from("Color blind"),
+            blocks: vec![
+                (BlockType::WALL, Look::Color(CB_BLUE)),
+                (BlockType::NODE, Look::Color(CB_YELLOW)),
+                (BlockType::HOLE, Look::Color(CB_SKY_BLUE)),
+                (BlockType::SLIDE, Look::Color(CB_GRAY))],
+            robots: ROBOT_TYPES.iter()
+                .map(|&robot_type| (robot_type, None,
+                                    Look::Color(robot_color(robot_type))))
+                .collect(),
+            bullets: vec![
+                (BulletOwner::Hero, Look::Color(WHITE)),
+                (BulletOwner::Enimy, Look::Color(CB_VERMILLION)),
+                (BulletOwner::Free, Look::Color(GRAY))],
+            ..Theme::default()
+        }
+    }
+
+    pub fn block(&self, block_type: BlockType) -> Look {
+        self.blocks.iter()
+            .find(|&&(theme_type, _)| theme_type == block_type)
+            .map_or(Look::Color(BLACK), |&(_, look)| look)
+    }
+
+    //look of robot for its direction, damaged robot flashes white
+    pub fn robot(&self, robot: &Robot) -> Look {
+        if robot.flashing() {
+            return Look::Color(WHITE);
+        }
+
+        let look = |direct: Option<Direct>| {
+            self.robots.iter()
+                .find(|&&(robot_type, theme_direct, _)| {
+                    robot_type == robot.robot_type && theme_direct == direct
+                })
+                .map(|&(_, _, look)| look)
+        };
+        look(Some(robot.direct))
+            .or_else(|| look(None))
+            .unwrap_or(Look::Color(robot.color()))
+    }
+
+    pub fn bullet(&self, owner: BulletOwner) -> Look {
+        self.bullets.iter()
+            .find(|&&(theme_owner, _)| theme_owner == owner)
+            .map_or(Look::Color(RED), |&(_, look)| look)
+    }
+
+    //directory of theme or None for the default theme,
+    //theme is based on the palette for color blindness if it's needed
+    pub fn load(dir: Option<&Path>, color_blind: bool)
+                                                -> Result<Theme, String> {
+        let palette = if color_blind {
+            Theme::color_blind()
+        } else {
+            Theme::default()
+        };
+        let dir = match dir {
+            Some(dir) => dir,
+            None => return Ok(palette)
+        };
+
+        let path = dir.join(THEME_FILE);
+        let text = fs::read_to_string(&path)
+            .map_err(|error| format!("Can't load theme from {}: {}",
+                                                    path.display(), error))?;
+        Theme::from_text(&text, dir, palette)
+    }
+
+    //records of theme, one per line, '#' starts comment:
+    //name NAME
+    //sheet FILE TILE_SIZE
+    //background R G B A
+    //block TYPE LOOK
+    //robot TYPE DIRECTION|any LOOK
+    //bullet [Hero|Enimy|Free] LOOK
+    //where LOOK is 'color R G B A' or 'tile COLUMN ROW'
+    pub fn from_text(text: &str, dir: &Path, palette: Theme)
+                                                -> Result<Theme, String> {
+        let mut lines = text.lines().enumerate();
+        match lines.next() {
+            Some((_, line)) if line.trim() == HEADER => {},
+            _ => return Err(String::from("It is not a theme of the game"))
+        }
+
+        let mut theme = palette;
+        theme.name = dir.file_name()
+                        .map_or(String::from("Theme"),
+                                |name| name.to_string_lossy().into_owned());
+
+        for (line_num, line) in lines {
+            let error = |message: String| {
+                format!("Line {} of theme: {}", line_num + 1, message)
+            };
+            let line = line.split('#').next().unwrap_or("");
+            let mut words = Words(line.split_whitespace());
+            let record = match words.next() {
+                Ok(record) => record,
+                Err(_) => continue //empty line
+            };
+
+            match record {
+                "name" => {
+                    let name: Vec<&str> = words.0.by_ref().collect();
+                    theme.name = name.join(" ");
+                },
+                "sheet" => {
+                    theme.sheet = Some(dir.join(words.next()
+                                                        .map_err(&error)?));
+                    theme.tile_size = words.parse().map_err(&error)?;
+                },
+                "background" => {
+                    theme.background = color(&mut words).map_err(&error)?;
+                },
+                "block" => {
+                    let block_type = words.variant(&BLOCK_TYPES)
+                                                        .map_err(&error)?;
+                    let look = look(&mut words).map_err(&error)?;
+                    theme.blocks.retain(|&(theme_type, _)|
+                                                    theme_type != block_type);
+                    theme.blocks.push((block_type, look));
+                },
+                "robot" => {
+                    let robot_type = words.variant(&ROBOT_TYPES)
+                                                        .map_err(&error)?;
+                    let direct = match words.next().map_err(&error)? {
+                        "any" => None,
+                        word => Some(variant(word, &DIRECTS)
+                                                        .map_err(&error)?)
+                    };
+                    let look = look(&mut words).map_err(&error)?;
+                    theme.robots.retain(|&(theme_type, theme_direct, _)|
+                        theme_type != robot_type || theme_direct != direct);
+                    theme.robots.push((robot_type, direct, look));
+                },
+                //bullets of all owners if owner isn't set
+                "bullet" => {
+                    let word = words.next().map_err(&error)?;
+                    let owner = variant(word, &BULLET_OWNERS).ok();
+                    let look = match owner {
+                        Some(_) => look(&mut words),
+                        None => look_of(word, &mut words)
+                    }.map_err(&error)?;
+                    for bullet in &mut theme.bullets {
+                        if owner.map_or(true, |owner| owner == bullet.0) {
+                            bullet.1 = look;
+                        }
+                    }
+                },
+                record => return Err(error(format!("unknown record '{}'",
+                                                                    record)))
+            }
+        }
+
+        let has_tiles = theme.blocks.iter().any(|&(_, look)| is_tile(look)) ||
+                    theme.robots.iter().any(|&(_, _, look)| is_tile(look)) ||
+                    theme.bullets.iter().any(|&(_, look)| is_tile(look));
+        if has_tiles && theme.sheet.is_none() {
+            return Err(String::from("Theme has tiles without sheet"));
+        }
+        Ok(theme)
+    }
+}
+
+//directories of themes which can be chosen
+pub fn available() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = match fs::read_dir(THEME_DIR) {
+        Ok(entries) => entries
+                        .filter_map(|entry| entry.ok())
+                        .map(|entry| entry.path())
+                        .filter(|path| path.join(THEME_FILE).is_file())
+                        .collect(),
+        Err(_) => vec![]
+    };
+    dirs.sort();
+    dirs
+}
+
+fn is_tile(look: Look) -> bool {
+    match look {
+        Look::Tile(_, _) => true,
+        Look::Color(_) => false
+    }
+}
+
+fn color(words: &mut Words) -> Result<[f32; 4], String> {
+    Ok([words.parse()?, words.parse()?, words.parse()?, words.parse()?])
+}
+
+fn look(words: &mut Words) -> Result<Look, String> {
+    let kind = words.next()?;
+    look_of(kind, words)
+}
+
+//look which kind is already read
+fn look_of(kind: &str, words: &mut Words) -> Result<Look, String> {
+    match kind {
+        "color" => Ok(Look::Color(color(words)?)),
+        "tile" => Ok(Look::Tile(words.parse()?, words.parse()?)),
+        word => Err(format!("unknown look '{}'", word))
+    }
+}
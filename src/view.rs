@@ -0,0 +1,71 @@
+//placement of the game in the window
+//
+//The game is drawn on the logical screen of constant size, it's scaled
+//to fit into the window keeping proportions and centered, free space
+//around it is left black. With integer scaling every logical pixel takes
+//the same whole count of pixels of the window, so tiles look crisp.
+
+//size of logical screen
+pub const LOGICAL_WIDTH: f64 = 640.0;
+pub const LOGICAL_HEIGHT: f64 = 480.0;
+
+#[derive(Debug)]
+#[derive(PartialEq)]
+#[derive(Clone, Copy)]
+pub struct View {
+    pub scale: f64, //size of logical pixel in coordinates of window
+    pub x: f64, //position of logical screen in coordinates of window
+    pub y: f64,
+    pub density: f64 //pixels in one unit of coordinates of window
+}
+
+impl Default for View {
+    fn default() -> View {
+        View { scale: 1.0, x: 0.0, y: 0.0, density: 1.0 }
+    }
+}
+
+impl View {
+    //window size is in its coordinates, draw size is in pixels
+    //(they differ on HiDPI screens)
+    pub fn fit(window_size: [f64; 2], draw_size: [u32; 2],
+                                                    integer: bool) -> View {
+        let (width, height) = (window_size[0], window_size[1]);
+        if width <= 0.0 || height <= 0.0 {
+            return View::default();
+        }
+        let density = (draw_size[0] as f64)/width;
+
+        //pixels in logical pixel
+        let mut pixels = (width*density/LOGICAL_WIDTH)
+                            .min(height*density/LOGICAL_HEIGHT);
+        //window smaller than logical screen is still scaled down
+        if integer && pixels >= 1.0 {
+            pixels = pixels.floor();
+        }
+
+        //logical screen starts at whole pixel
+        let x = ((width*density - LOGICAL_WIDTH*pixels)/2.0).floor();
+        let y = ((height*density - LOGICAL_HEIGHT*pixels)/2.0).floor();
+        View {
+            scale: pixels/density,
+            x: x/density,
+            y: y/density,
+            density: density
+        }
+    }
+
+    //pixels of window in one logical pixel
+    pub fn pixel_scale(&self) -> f64 {
+        self.scale*self.density
+    }
+
+    //rectangle of logical screen in pixels of window
+    pub fn pixel_rect(&self, rect: [f64; 4]) -> [u32; 4] {
+        let pixels = self.pixel_scale();
+        let x = self.x*self.density + rect[0]*pixels;
+        let y = self.y*self.density + rect[1]*pixels;
+        [x.round().max(0.0) as u32, y.round().max(0.0) as u32,
+            (rect[2]*pixels).round() as u32, (rect[3]*pixels).round() as u32]
+    }
+}